@@ -0,0 +1,47 @@
+#![feature(test)]
+
+extern crate test;
+
+use libsecp256k1::curve::Field;
+use rand::RngCore;
+use test::{black_box, Bencher};
+
+fn random_field() -> Field {
+    loop {
+        let mut b32 = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut b32);
+        let mut f = Field::default();
+        if f.set_b32(&b32) {
+            return f;
+        }
+    }
+}
+
+// `*` and `sqr` use the x86-bmi2 backend when it is enabled and supported
+// by the CPU, while `mul_const` and `sqr_const` always use the portable code.
+
+#[bench]
+fn bench_field_mul(b: &mut Bencher) {
+    let x = random_field();
+    let y = random_field();
+    b.iter(|| black_box(x) * black_box(y));
+}
+
+#[bench]
+fn bench_field_mul_portable(b: &mut Bencher) {
+    let x = random_field();
+    let y = random_field();
+    b.iter(|| black_box(x).mul_const(&black_box(y)));
+}
+
+#[bench]
+fn bench_field_sqr(b: &mut Bencher) {
+    let x = random_field();
+    b.iter(|| black_box(x).sqr());
+}
+
+#[bench]
+fn bench_field_sqr_portable(b: &mut Bencher) {
+    let x = random_field();
+    b.iter(|| black_box(x).sqr_const());
+}
@@ -2,7 +2,7 @@
 
 extern crate test;
 
-use libsecp256k1::PublicKey;
+use libsecp256k1::{PublicKey, PublicKeySequence, SecretKey};
 use secp256k1_test::{rand::thread_rng, Secp256k1};
 use test::Bencher;
 
@@ -46,3 +46,37 @@ fn bench_public_key_serialize_compressed(b: &mut Bencher) {
         let _serialized = pubkey.serialize_compressed();
     });
 }
+
+#[bench]
+fn bench_public_key_from_secret_key_64(b: &mut Bencher) {
+    let seckeys: Vec<SecretKey> = (0..64)
+        .map(|_| SecretKey::random(&mut rand::rngs::OsRng))
+        .collect();
+    b.iter(|| {
+        let _pubkeys: Vec<PublicKey> = seckeys.iter().map(PublicKey::from_secret_key).collect();
+    });
+}
+
+#[bench]
+fn bench_public_key_from_secret_keys_batch_64(b: &mut Bencher) {
+    let seckeys: Vec<SecretKey> = (0..64)
+        .map(|_| SecretKey::random(&mut rand::rngs::OsRng))
+        .collect();
+    b.iter(|| {
+        let _pubkeys = PublicKey::from_secret_keys_batch(&seckeys);
+    });
+}
+
+#[bench]
+fn bench_public_key_sequence_64(b: &mut Bencher) {
+    let start = PublicKey::from_secret_key(&SecretKey::random(&mut rand::rngs::OsRng));
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let step = SecretKey::parse(&one).unwrap();
+    let mut sequence = PublicKeySequence::new(&start, &step);
+    b.iter(|| {
+        for _ in 0..64 {
+            let _pubkey = sequence.next().unwrap();
+        }
+    });
+}
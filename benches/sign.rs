@@ -3,7 +3,7 @@
 extern crate test;
 
 use arrayref::array_ref;
-use libsecp256k1::{sign, Message, SecretKey};
+use libsecp256k1::{sign, sign_batch, sign_hardened, Hardening, Message, SecretKey};
 use secp256k1_test::{rand::thread_rng, Secp256k1};
 use test::Bencher;
 
@@ -18,3 +18,48 @@ fn bench_sign_message(b: &mut Bencher) {
         let _ = sign(&message, &seckey);
     });
 }
+
+#[bench]
+fn bench_sign_hardened(b: &mut Bencher) {
+    let message = Message::parse(&[5u8; 32]);
+    let seckey = SecretKey::random(&mut rand::rngs::OsRng);
+    let mut rng = rand::rngs::OsRng;
+
+    b.iter(|| {
+        let _ = sign_hardened(&message, &seckey, &mut rng, Hardening::default());
+    });
+}
+
+#[bench]
+fn bench_sign_message_64(b: &mut Bencher) {
+    let items: Vec<(Message, SecretKey)> = (0..64u8)
+        .map(|i| {
+            (
+                Message::parse(&[i; 32]),
+                SecretKey::random(&mut rand::rngs::OsRng),
+            )
+        })
+        .collect();
+
+    b.iter(|| {
+        for (message, seckey) in &items {
+            let _ = sign(message, seckey);
+        }
+    });
+}
+
+#[bench]
+fn bench_sign_batch_64(b: &mut Bencher) {
+    let items: Vec<(Message, SecretKey)> = (0..64u8)
+        .map(|i| {
+            (
+                Message::parse(&[i; 32]),
+                SecretKey::random(&mut rand::rngs::OsRng),
+            )
+        })
+        .collect();
+
+    b.iter(|| {
+        let _ = sign_batch(&items);
+    });
+}
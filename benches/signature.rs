@@ -2,7 +2,10 @@
 
 extern crate test;
 
-use libsecp256k1::Signature;
+use libsecp256k1::{
+    recover, recover_batch, sign, verify, verify_prepared, Message, PreparedPublicKey, PublicKey,
+    RecoveryId, SecretKey, Signature,
+};
 use secp256k1_test::{rand::thread_rng, Message as SecpMessage, Secp256k1};
 use test::Bencher;
 
@@ -40,3 +43,65 @@ fn bench_signature_serialize(b: &mut Bencher) {
         let _serialized = signature.serialize();
     });
 }
+
+fn verify_fixture() -> (Message, Signature, PublicKey) {
+    let secp256k1 = Secp256k1::new();
+    let message_arr = [5u8; 32];
+    let (privkey, pubkey) = secp256k1.generate_keypair(&mut thread_rng());
+    let message = SecpMessage::from_slice(&message_arr).unwrap();
+    let signature = secp256k1.sign(&message, &privkey);
+    let signature = Signature::parse_standard_slice(&signature.serialize_compact()).unwrap();
+    let pubkey = PublicKey::parse(&pubkey.serialize_uncompressed()).unwrap();
+
+    (Message::parse(&message_arr), signature, pubkey)
+}
+
+#[bench]
+fn bench_verify(b: &mut Bencher) {
+    let (message, signature, pubkey) = verify_fixture();
+
+    b.iter(|| {
+        assert!(verify(&message, &signature, &pubkey));
+    });
+}
+
+#[bench]
+fn bench_verify_prepared(b: &mut Bencher) {
+    let (message, signature, pubkey) = verify_fixture();
+    let prepared = PreparedPublicKey::new(&pubkey);
+
+    b.iter(|| {
+        assert!(verify_prepared(&message, &signature, &prepared));
+    });
+}
+
+fn recover_fixture(count: u8) -> Vec<(Message, Signature, RecoveryId)> {
+    (0..count)
+        .map(|i| {
+            let message = Message::parse(&[i; 32]);
+            let seckey = SecretKey::random(&mut rand::rngs::OsRng);
+            let (signature, recid) = sign(&message, &seckey);
+            (message, signature, recid)
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_recover_64(b: &mut Bencher) {
+    let items = recover_fixture(64);
+
+    b.iter(|| {
+        for (message, signature, recid) in &items {
+            let _ = recover(message, signature, recid);
+        }
+    });
+}
+
+#[bench]
+fn bench_recover_batch_64(b: &mut Bencher) {
+    let items = recover_fixture(64);
+
+    b.iter(|| {
+        let _ = recover_batch(&items);
+    });
+}
@@ -1,6 +1,28 @@
+#[cfg(all(
+    feature = "static-context",
+    not(feature = "lazy-static-context"),
+    not(feature = "const-context")
+))]
 use std::{env, fs::File, io::Write, path::Path};
 
 fn main() {
+    // The tables are only included by the build-script based
+    // `static-context`. The other contexts compute them in const context or
+    // at runtime.
+    #[cfg(all(
+        feature = "static-context",
+        not(feature = "lazy-static-context"),
+        not(feature = "const-context")
+    ))]
+    generate();
+}
+
+#[cfg(all(
+    feature = "static-context",
+    not(feature = "lazy-static-context"),
+    not(feature = "const-context")
+))]
+fn generate() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
 
     let const_path = Path::new(&out_dir).join("const.rs");
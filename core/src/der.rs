@@ -3,7 +3,10 @@ use core::{
     mem,
 };
 
-use crate::{error::Error, scalar::Scalar};
+use crate::{
+    error::{DerError, Error},
+    scalar::Scalar,
+};
 
 pub struct SignatureArray([u8; 6 + 33 + 33], usize);
 
@@ -33,11 +36,31 @@ impl AsMut<[u8]> for SignatureArray {
     }
 }
 
-pub struct Decoder<'a>(&'a [u8], usize);
+/// A reader of DER-encoded signatures and keys. It counts the signature
+/// integers it reads, so that errors can tell R from S.
+pub struct Decoder<'a>(&'a [u8], usize, usize);
 
 impl<'a> Decoder<'a> {
     pub fn new(arr: &'a [u8]) -> Self {
-        Decoder(arr, 0)
+        Decoder(arr, 0, 0)
+    }
+
+    /// The error for an invalid length, of the sequence before any integer
+    /// is read, and of R or S after that.
+    fn length_error(&self) -> Error {
+        match self.2 {
+            0 => DerError::InvalidSequenceLength.into(),
+            1 => DerError::InvalidRLength.into(),
+            _ => DerError::InvalidSLength.into(),
+        }
+    }
+
+    fn integer_error(&self, r: DerError, s: DerError) -> Error {
+        if self.2 <= 1 {
+            r.into()
+        } else {
+            s.into()
+        }
     }
 
     pub fn remaining_len(&self) -> usize {
@@ -46,7 +69,7 @@ impl<'a> Decoder<'a> {
 
     pub fn read(&mut self) -> Result<u8, Error> {
         if self.1 >= self.0.len() {
-            Err(Error::InvalidSignature)
+            Err(DerError::TooShort.into())
         } else {
             let v = self.0[self.1];
             self.1 += 1;
@@ -56,7 +79,7 @@ impl<'a> Decoder<'a> {
 
     pub fn peek(&self, forward: usize) -> Result<u8, Error> {
         if self.1 + forward >= self.0.len() {
-            Err(Error::InvalidSignature)
+            Err(DerError::TooShort.into())
         } else {
             let v = self.0[self.1 + forward];
             Ok(v)
@@ -65,7 +88,7 @@ impl<'a> Decoder<'a> {
 
     pub fn peek_slice(&self, len: usize) -> Result<&[u8], Error> {
         if (len == 0 && self.1 >= self.0.len()) || self.1 + len > self.0.len() {
-            Err(Error::InvalidSignature)
+            Err(DerError::TooShort.into())
         } else {
             let v = &self.0[self.1..(self.1 + len)];
             Ok(v)
@@ -74,7 +97,7 @@ impl<'a> Decoder<'a> {
 
     pub fn skip(&mut self, len: usize) -> Result<(), Error> {
         if (len == 0 && self.1 >= self.0.len()) || self.1 + len > self.0.len() {
-            Err(Error::InvalidSignature)
+            Err(DerError::TooShort.into())
         } else {
             self.1 += len;
             Ok(())
@@ -86,14 +109,17 @@ impl<'a> Decoder<'a> {
         if v == 0x30 {
             Ok(())
         } else {
-            Err(Error::InvalidSignature)
+            Err(DerError::NotSequence.into())
         }
     }
 
+    /// Read a DER length, as `secp256k1_der_read_len` of libsecp256k1. A
+    /// long-form length must be minimally encoded, and may fill at most the
+    /// rest of the input.
     pub fn read_len(&mut self) -> Result<usize, Error> {
         let b1 = self.read()?;
         if b1 == 0xff {
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         // Short form
@@ -103,56 +129,61 @@ impl<'a> Decoder<'a> {
 
         // Infinite length is not allowed
         if b1 == 0x80 {
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         let mut lenleft = (b1 & 0x7f) as usize;
         if lenleft > self.remaining_len() {
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         if self.peek(0)? == 0 {
             // Not the shortest possible length encoding
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         if lenleft > mem::size_of::<usize>() {
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         let mut ret = 0;
         while lenleft > 0 {
             ret = (ret << 8) | (self.read()? as usize);
+            // Only the length bytes still to read come before the contents.
+            lenleft -= 1;
             if ret + lenleft > self.remaining_len() {
-                return Err(Error::InvalidSignature);
+                return Err(self.length_error());
             }
-            lenleft -= 1;
         }
 
         if ret < 128 {
             // Not the shortest possible length encoding
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         Ok(ret)
     }
 
     pub fn read_integer(&mut self) -> Result<Scalar, Error> {
+        self.2 += 1;
         if self.read()? != 0x02 {
-            return Err(Error::InvalidSignature);
+            return Err(self.integer_error(DerError::RNotInteger, DerError::SNotInteger));
         }
 
         let mut rlen = self.read_len()?;
-        if rlen == 0 || rlen > self.remaining_len() {
-            return Err(Error::InvalidSignature);
+        if rlen == 0 {
+            return Err(self.integer_error(DerError::RZeroLength, DerError::SZeroLength));
+        }
+        if rlen > self.remaining_len() {
+            return Err(self.length_error());
         }
 
         if self.peek(0)? == 0x00 && rlen > 1 && (self.peek(1)? & 0x80) == 0x00 {
-            return Err(Error::InvalidSignature);
+            return Err(self.integer_error(DerError::RExcessPadding, DerError::SExcessPadding));
         }
 
         if self.peek(0)? == 0xff && rlen > 1 && (self.peek(1)? & 0x80) == 0x00 {
-            return Err(Error::InvalidSignature);
+            return Err(self.integer_error(DerError::RExcessPadding, DerError::SExcessPadding));
         }
 
         let mut overflow = false;
@@ -192,7 +223,7 @@ impl<'a> Decoder<'a> {
         if len & 0x80 != 0x00 {
             len -= 0x80;
             if len as usize > self.remaining_len() {
-                return Err(Error::InvalidSignature);
+                return Err(self.length_error());
             }
             self.skip(len as usize)?;
         }
@@ -206,14 +237,14 @@ impl<'a> Decoder<'a> {
         if len & 0x80 != 0x00 {
             len -= 0x80;
             if len as usize > self.remaining_len() {
-                return Err(Error::InvalidSignature);
+                return Err(self.length_error());
             }
             while len > 0 && self.peek(0)? == 0 {
                 self.skip(1)?;
                 len -= 1;
             }
             if (len as usize) >= mem::size_of::<usize>() {
-                return Err(Error::InvalidSignature);
+                return Err(self.length_error());
             }
             while len > 0 {
                 ret = (ret << 8) + (self.read()? as usize);
@@ -223,16 +254,17 @@ impl<'a> Decoder<'a> {
             ret = len as usize;
         }
         if ret > self.remaining_len() {
-            return Err(Error::InvalidSignature);
+            return Err(self.length_error());
         }
 
         Ok(ret)
     }
 
     pub fn read_integer_lax(&mut self) -> Result<Scalar, Error> {
+        self.2 += 1;
         // Integer tag byte.
         if self.read()? != 0x02 {
-            return Err(Error::InvalidSignature);
+            return Err(self.integer_error(DerError::RNotInteger, DerError::SNotInteger));
         }
 
         let mut len = self.read_len_lax()?;
@@ -265,4 +297,157 @@ impl<'a> Decoder<'a> {
 
         Ok(int)
     }
+
+    /// Read an element with the given tag and a DER length, and return its
+    /// contents.
+    pub fn read_element(&mut self, tag: u8) -> Result<&'a [u8], Error> {
+        if self.read()? != tag {
+            return Err(DerError::UnexpectedTag.into());
+        }
+
+        let len = self
+            .read_len()
+            .map_err(|_| Error::from(DerError::InvalidLength))?;
+        if len > self.remaining_len() {
+            return Err(DerError::InvalidLength.into());
+        }
+
+        let v = &self.0[self.1..(self.1 + len)];
+        self.1 += len;
+        Ok(v)
+    }
+
+    /// Read an element only if the next tag is `tag`, as for an optional
+    /// field.
+    pub fn read_optional_element(&mut self, tag: u8) -> Result<Option<&'a [u8]>, Error> {
+        if self.remaining_len() > 0 && self.peek(0)? == tag {
+            self.read_element(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Read a minimally encoded, non-negative integer that fits in a `u32`,
+    /// such as a version or an iteration count.
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = self.read_element(0x02)?;
+        if bytes.is_empty() || bytes[0] & 0x80 != 0 {
+            return Err(DerError::InvalidInteger.into());
+        }
+        if bytes.len() > 1 && bytes[0] == 0x00 {
+            if bytes[1] & 0x80 == 0 {
+                return Err(DerError::InvalidInteger.into());
+            }
+            bytes = &bytes[1..];
+        }
+        if bytes.len() > 4 {
+            return Err(DerError::InvalidInteger.into());
+        }
+
+        Ok(bytes.iter().fold(0, |acc, b| (acc << 8) | *b as u32))
+    }
+
+    /// Read a bit string without unused bits, and return its bytes.
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], Error> {
+        match self.read_element(0x03)?.split_first() {
+            Some((0, bytes)) => Ok(bytes),
+            _ => Err(DerError::InvalidBitString.into()),
+        }
+    }
+
+    /// Check that the whole input has been read.
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.remaining_len() == 0 {
+            Ok(())
+        } else {
+            Err(DerError::TrailingData.into())
+        }
+    }
+}
+
+/// Check that `sig` is a DER-encoded signature following exactly the rules
+/// of BIP-66, without a trailing sighash byte, and report the first rule it
+/// violates. This only looks at the encoding, so R and S may still be out
+/// of range.
+pub fn check_der_strict(sig: &[u8]) -> Result<(), DerError> {
+    // Format: 0x30 [total-length] 0x02 [R-length] [R] 0x02 [S-length] [S]
+    if sig.len() < 8 {
+        return Err(DerError::TooShort);
+    }
+    if sig.len() > 72 {
+        return Err(DerError::TooLong);
+    }
+    if sig[0] != 0x30 {
+        return Err(DerError::NotSequence);
+    }
+    if sig[1] as usize != sig.len() - 2 {
+        return Err(DerError::InvalidSequenceLength);
+    }
+
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return Err(DerError::InvalidRLength);
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 6 != sig.len() {
+        return Err(DerError::InvalidSLength);
+    }
+
+    if sig[2] != 0x02 {
+        return Err(DerError::RNotInteger);
+    }
+    if len_r == 0 {
+        return Err(DerError::RZeroLength);
+    }
+    if sig[4] & 0x80 != 0 {
+        return Err(DerError::RNegative);
+    }
+    if len_r > 1 && sig[4] == 0x00 && sig[5] & 0x80 == 0 {
+        return Err(DerError::RExcessPadding);
+    }
+
+    if sig[len_r + 4] != 0x02 {
+        return Err(DerError::SNotInteger);
+    }
+    if len_s == 0 {
+        return Err(DerError::SZeroLength);
+    }
+    if sig[len_r + 6] & 0x80 != 0 {
+        return Err(DerError::SNegative);
+    }
+    if len_s > 1 && sig[len_r + 6] == 0x00 && sig[len_r + 7] & 0x80 == 0 {
+        return Err(DerError::SExcessPadding);
+    }
+
+    Ok(())
+}
+
+/// Parse R and S from a signature accepted by `check_der_strict`. Like the
+/// other DER parsers, a value that is not below the group order is parsed
+/// as zero, which never verifies.
+pub fn parse_der_strict(sig: &[u8]) -> Result<(Scalar, Scalar), DerError> {
+    check_der_strict(sig)?;
+
+    let len_r = sig[3] as usize;
+    let r = der_integer_to_scalar(&sig[4..(4 + len_r)]);
+    let s = der_integer_to_scalar(&sig[(6 + len_r)..]);
+
+    Ok((r, s))
+}
+
+fn der_integer_to_scalar(mut bytes: &[u8]) -> Scalar {
+    while !bytes.is_empty() && bytes[0] == 0 {
+        bytes = &bytes[1..];
+    }
+
+    let mut int = Scalar::default();
+    if bytes.len() <= 32 {
+        let mut b32 = [0u8; 32];
+        b32[(32 - bytes.len())..].copy_from_slice(bytes);
+        if bool::from(int.set_b32(&b32)) {
+            int = Scalar::default();
+        }
+    }
+
+    int
 }
@@ -1,9 +1,11 @@
 use crate::{
-    ecmult::ECMultContext,
+    ecmult::{wipe, ECMultContext},
     group::{Affine, Jacobian},
     scalar::Scalar,
 };
-use digest::{generic_array::GenericArray, Digest};
+use core::convert::TryInto;
+use digest::{generic_array::GenericArray, Digest, FixedOutput, Update};
+use zeroize::Zeroize;
 
 impl ECMultContext {
     pub fn ecdh_raw<D: Digest + Default>(
@@ -13,8 +15,42 @@ impl ECMultContext {
     ) -> Option<GenericArray<u8, D::OutputSize>> {
         let mut digest: D = Default::default();
 
+        let mut x = self.ecdh_point(point, scalar)?;
+        Digest::update(&mut digest, &x[..]);
+        x.zeroize();
+        let ret = digest.finalize_reset();
+        wipe(&mut digest, D::default());
+        Some(ret)
+    }
+
+    /// Same as `ecdh_raw`, but write the hash into `out`, so that it can be
+    /// placed in locked memory. The hasher is not copied, and its state is
+    /// wiped afterwards. Returns `false` if `scalar` is zero, in which case
+    /// `out` is unchanged.
+    pub fn ecdh_raw_into<D: Update + FixedOutput + Default>(
+        &self,
+        point: &Affine,
+        scalar: &Scalar,
+        out: &mut GenericArray<u8, D::OutputSize>,
+    ) -> bool {
+        let mut digest: D = Default::default();
+
+        let mut x = match self.ecdh_point(point, scalar) {
+            Some(x) => x,
+            None => return false,
+        };
+        Update::update(&mut digest, &x[..]);
+        x.zeroize();
+        digest.finalize_into_reset(out);
+        wipe(&mut digest, D::default());
+        true
+    }
+
+    /// Compressed encoding of `scalar * point`, which is hashed into the
+    /// shared secret.
+    fn ecdh_point(&self, point: &Affine, scalar: &Scalar) -> Option<[u8; 33]> {
         let mut pt = *point;
-        let s = *scalar;
+        let mut s = *scalar;
 
         if s.is_zero() {
             return None;
@@ -23,15 +59,17 @@ impl ECMultContext {
         let mut res = Jacobian::default();
         self.ecmult_const(&mut res, &pt, &s);
         pt.set_gej(&res);
+        res.clear();
+        s.clear();
 
         pt.x.normalize();
         pt.y.normalize();
 
-        let x = pt.x.b32();
-        let y = 0x02 | (if pt.y.is_odd() { 1 } else { 0 });
-
-        digest.update(&[y]);
-        digest.update(&x);
-        Some(digest.finalize_reset())
+        let mut ret = [0u8; 33];
+        ret[0] = 0x02 | (if pt.y.is_odd() { 1 } else { 0 });
+        let (_, x) = ret.split_at_mut(1);
+        pt.x.fill_b32(x.try_into().unwrap());
+        pt.clear();
+        Some(ret)
     }
 }
@@ -1,10 +1,14 @@
+#[cfg(feature = "alloc")]
+use crate::ecmult::set_all_gej_var;
 use crate::{
     ecmult::{ECMultContext, ECMultGenContext},
     field::Field,
-    group::{Affine, Jacobian},
+    group::{Affine, AffineStorage, Jacobian},
     scalar::Scalar,
-    Error,
+    Error, ErrorDetail, ErrorKind, VerifyError,
 };
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 
 const P_MINUS_ORDER: Field = Field::new(0, 0, 0, 1, 0x45512319, 0x50B75FC4, 0x402DA172, 0x2FC9BAEE);
 
@@ -12,6 +16,58 @@ const ORDER_AS_FE: Field = Field::new(
     0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFE, 0xBAAEDCE6, 0xAF48A03B, 0xBFD25E8C, 0xD0364141,
 );
 
+/// Check that the x coordinate of `pr` is `sigr` modulo the group order.
+fn check_sigr(pr: &Jacobian, sigr: &Scalar) -> bool {
+    if pr.is_infinity() {
+        return false;
+    }
+
+    let c = sigr.b32();
+    let mut xr: Field = Default::default();
+    let _ = xr.set_b32(&c);
+
+    if pr.eq_x_var(&xr) {
+        return true;
+    }
+    if xr >= P_MINUS_ORDER {
+        return false;
+    }
+    xr += ORDER_AS_FE;
+    if pr.eq_x_var(&xr) {
+        return true;
+    }
+    false
+}
+
+/// Compute the point `R` of a recoverable signature from `sigr` and the
+/// recovery id.
+fn recover_point(sigr: &Scalar, sigs: &Scalar, rec_id: u8) -> Result<Affine, Error> {
+    debug_assert!(rec_id < 4);
+
+    if sigr.is_zero() || sigs.is_zero() {
+        return Err(Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::ZeroScalar));
+    }
+
+    let brx = sigr.b32();
+    let mut fx = Field::default();
+    let overflow = fx.set_b32(&brx);
+    debug_assert!(overflow);
+
+    if rec_id & 2 > 0 {
+        if fx >= P_MINUS_ORDER {
+            return Err(
+                Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::OutOfRange)
+            );
+        }
+        fx += ORDER_AS_FE;
+    }
+    let mut x = Affine::default();
+    if !x.set_xo_var(&fx, rec_id & 1 > 0) {
+        return Err(Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::NotOnCurve));
+    }
+    Ok(x)
+}
+
 impl ECMultContext {
     pub fn verify_raw(
         &self,
@@ -20,11 +76,34 @@ impl ECMultContext {
         pubkey: &Affine,
         message: &Scalar,
     ) -> bool {
-        let c;
+        self.verify_raw_detailed(sigr, sigs, pubkey, message, false)
+            .is_ok()
+    }
+
+    /// Same as `verify_raw`, but return why the signature does not verify.
+    /// With `require_low_s`, signatures whose `sigs` is in the upper half
+    /// of the group order are rejected, as in BIP-146 and EIP-2.
+    pub fn verify_raw_detailed(
+        &self,
+        sigr: &Scalar,
+        sigs: &Scalar,
+        pubkey: &Affine,
+        message: &Scalar,
+        require_low_s: bool,
+    ) -> Result<(), VerifyError> {
         let (sn, u1, u2): (Scalar, Scalar, Scalar);
 
-        if sigr.is_zero() || sigs.is_zero() {
-            return false;
+        if sigr.is_zero() {
+            return Err(VerifyError::ZeroR);
+        }
+        if sigs.is_zero() {
+            return Err(VerifyError::ZeroS);
+        }
+        if require_low_s && sigs.is_high() {
+            return Err(VerifyError::HighS);
+        }
+        if pubkey.is_infinity() {
+            return Err(VerifyError::InfinityPublicKey);
         }
 
         sn = sigs.inv_var();
@@ -34,25 +113,37 @@ impl ECMultContext {
         pubkeyj.set_ge(pubkey);
         let mut pr: Jacobian = Jacobian::default();
         self.ecmult(&mut pr, &pubkeyj, &u2, &u1);
-        if pr.is_infinity() {
-            return false;
+
+        if check_sigr(&pr, sigr) {
+            Ok(())
+        } else {
+            Err(VerifyError::Mismatch)
         }
+    }
 
-        c = sigr.b32();
-        let mut xr: Field = Default::default();
-        let _ = xr.set_b32(&c);
+    /// Same as `verify_raw`, with the public key given as a table of odd
+    /// multiples for window `window`. See `ECMultContext::ecmult_prepared`.
+    pub fn verify_raw_prepared(
+        &self,
+        sigr: &Scalar,
+        sigs: &Scalar,
+        pubkey_table: &[AffineStorage],
+        window: usize,
+        message: &Scalar,
+    ) -> bool {
+        let (sn, u1, u2): (Scalar, Scalar, Scalar);
 
-        if pr.eq_x_var(&xr) {
-            return true;
-        }
-        if xr >= P_MINUS_ORDER {
+        if sigr.is_zero() || sigs.is_zero() {
             return false;
         }
-        xr += ORDER_AS_FE;
-        if pr.eq_x_var(&xr) {
-            return true;
-        }
-        false
+
+        sn = sigs.inv_var();
+        u1 = &sn * message;
+        u2 = &sn * sigr;
+        let mut pr: Jacobian = Jacobian::default();
+        self.ecmult_prepared(&mut pr, pubkey_table, window, &u2, &u1);
+
+        check_sigr(&pr, sigr)
     }
 
     pub fn recover_raw(
@@ -62,27 +153,7 @@ impl ECMultContext {
         rec_id: u8,
         message: &Scalar,
     ) -> Result<Affine, Error> {
-        debug_assert!(rec_id < 4);
-
-        if sigr.is_zero() || sigs.is_zero() {
-            return Err(Error::InvalidSignature);
-        }
-
-        let brx = sigr.b32();
-        let mut fx = Field::default();
-        let overflow = fx.set_b32(&brx);
-        debug_assert!(overflow);
-
-        if rec_id & 2 > 0 {
-            if fx >= P_MINUS_ORDER {
-                return Err(Error::InvalidSignature);
-            }
-            fx += ORDER_AS_FE;
-        }
-        let mut x = Affine::default();
-        if !x.set_xo_var(&fx, rec_id & 1 > 0) {
-            return Err(Error::InvalidSignature);
-        }
+        let x = recover_point(sigr, sigs, rec_id)?;
         let mut xj = Jacobian::default();
         xj.set_ge(&x);
         let rn = sigr.inv();
@@ -96,11 +167,115 @@ impl ECMultContext {
         pubkey.set_gej_var(&qj);
 
         if pubkey.is_infinity() {
-            Err(Error::InvalidSignature)
+            Err(Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::Infinity))
         } else {
             Ok(pubkey)
         }
     }
+
+    /// Same as `recover_raw` for many signatures at once, with the same
+    /// results, including the errors. The inverses of `sigr` and the
+    /// affine conversions of the recovered keys each share a single
+    /// inversion. The square root that decompresses every `R` is still
+    /// computed on its own: unlike inverses, square roots cannot be derived
+    /// from the one of a product. The four slices must have the same
+    /// length. Not constant time.
+    #[cfg(feature = "alloc")]
+    pub fn recover_raw_batch(
+        &self,
+        sigrs: &[Scalar],
+        sigss: &[Scalar],
+        rec_ids: &[u8],
+        messages: &[Scalar],
+    ) -> Vec<Result<Affine, Error>> {
+        let len = sigrs.len();
+        assert!(sigss.len() == len && rec_ids.len() == len && messages.len() == len);
+
+        let mut points: Vec<Result<Affine, Error>> = Vec::with_capacity(len);
+        for i in 0..len {
+            points.push(recover_point(&sigrs[i], &sigss[i], rec_ids[i]));
+        }
+
+        // Invert the `sigr` of every valid item with Montgomery's trick.
+        // Each slot holds the product of the previous valid `sigr` values.
+        let mut rns = vec![Scalar::default(); len];
+        let mut acc = Scalar::from_int(1);
+        for i in 0..len {
+            if points[i].is_ok() {
+                rns[i] = acc;
+                acc *= &sigrs[i];
+            }
+        }
+        let mut inv = acc.inv_var();
+        for i in (0..len).rev() {
+            if points[i].is_ok() {
+                rns[i] *= &inv;
+                inv *= &sigrs[i];
+            }
+        }
+
+        let mut qjs = vec![Jacobian::default(); len];
+        for i in 0..len {
+            match &points[i] {
+                Ok(x) => {
+                    let mut xj = Jacobian::default();
+                    xj.set_ge(x);
+                    let mut u1 = rns[i] * messages[i];
+                    u1 = -u1;
+                    let u2 = rns[i] * sigss[i];
+                    self.ecmult(&mut qjs[i], &xj, &u2, &u1);
+                }
+                Err(_) => qjs[i].set_infinity(),
+            }
+        }
+
+        let pubkeys = set_all_gej_var(&qjs);
+        points
+            .into_iter()
+            .zip(pubkeys)
+            .map(|(point, pubkey)| {
+                point?;
+                if pubkey.is_infinity() {
+                    Err(Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::Infinity))
+                } else {
+                    Ok(pubkey)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Finish a signature given `R` in affine coordinates and the inverse of
+/// the nonce. Clears `r`.
+fn sign_finish(
+    r: &mut Affine,
+    seckey: &Scalar,
+    message: &Scalar,
+    nonce_inv: &Scalar,
+) -> Result<(Scalar, Scalar, u8), Error> {
+    r.x.normalize();
+    r.y.normalize();
+    let b = r.x.b32();
+    let mut sigr = Scalar::default();
+    let overflow = bool::from(sigr.set_b32(&b));
+    debug_assert!(!sigr.is_zero());
+    debug_assert!(!overflow);
+
+    let mut recid = (if overflow { 2 } else { 0 }) | (if r.y.is_odd() { 1 } else { 0 });
+    let mut n = &sigr * seckey;
+    n += message;
+    let mut sigs = *nonce_inv;
+    sigs *= &n;
+    n.clear();
+    r.clear();
+    if sigs.is_zero() {
+        return Err(Error::new(ErrorKind::InvalidMessage).with_detail(ErrorDetail::ZeroScalar));
+    }
+    if sigs.is_high() {
+        sigs = -sigs;
+        recid ^= 1;
+    }
+    Ok((sigr, sigs, recid))
 }
 
 impl ECMultGenContext {
@@ -114,29 +289,78 @@ impl ECMultGenContext {
         self.ecmult_gen(&mut rp, nonce);
         let mut r = Affine::default();
         r.set_gej(&rp);
-        r.x.normalize();
-        r.y.normalize();
-        let b = r.x.b32();
-        let mut sigr = Scalar::default();
-        let overflow = bool::from(sigr.set_b32(&b));
-        debug_assert!(!sigr.is_zero());
-        debug_assert!(!overflow);
-
-        let mut recid = (if overflow { 2 } else { 0 }) | (if r.y.is_odd() { 1 } else { 0 });
-        let mut n = &sigr * seckey;
-        n += message;
-        let mut sigs = nonce.inv();
-        sigs *= &n;
-        n.clear();
         rp.clear();
-        r.clear();
-        if sigs.is_zero() {
-            return Err(Error::InvalidMessage);
+        let mut nonce_inv = nonce.inv();
+        let result = sign_finish(&mut r, seckey, message, &nonce_inv);
+        nonce_inv.clear();
+        result
+    }
+
+    /// Same as `sign_raw` for many signatures at once. The nonce inverses
+    /// and the affine conversions of `R` each share a single inversion,
+    /// using Montgomery's trick. Constant time. The three slices must have
+    /// the same length, and every nonce must be non-zero.
+    #[cfg(feature = "alloc")]
+    pub fn sign_raw_batch(
+        &self,
+        seckeys: &[Scalar],
+        messages: &[Scalar],
+        nonces: &[Scalar],
+    ) -> Vec<Result<(Scalar, Scalar, u8), Error>> {
+        assert!(seckeys.len() == nonces.len() && messages.len() == nonces.len());
+        let len = nonces.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        let mut rps: Vec<Jacobian> = Vec::with_capacity(len);
+        for nonce in nonces {
+            debug_assert!(!nonce.is_zero());
+            let mut rp = Jacobian::default();
+            self.ecmult_gen(&mut rp, nonce);
+            rps.push(rp);
+        }
+
+        // Running products of the z coordinates and of the nonces.
+        let mut zprod: Vec<Field> = Vec::with_capacity(len);
+        let mut kprod: Vec<Scalar> = Vec::with_capacity(len);
+        zprod.push(rps[0].z);
+        kprod.push(nonces[0]);
+        for i in 1..len {
+            zprod.push(zprod[i - 1] * rps[i].z);
+            kprod.push(kprod[i - 1] * nonces[i]);
+        }
+
+        let mut zinv = zprod[len - 1].inv();
+        let mut kinv = kprod[len - 1].inv();
+        let mut results = Vec::with_capacity(len);
+        let mut r = Affine::default();
+        for i in (0..len).rev() {
+            let (mut zi, mut ki) = if i > 0 {
+                (zinv * zprod[i - 1], kinv * kprod[i - 1])
+            } else {
+                (zinv, kinv)
+            };
+            if i > 0 {
+                zinv *= rps[i].z;
+                kinv *= &nonces[i];
+            }
+            r.set_gej_zinv(&rps[i], &zi);
+            results.push(sign_finish(&mut r, &seckeys[i], &messages[i], &ki));
+            zi.clear();
+            ki.clear();
+            rps[i].clear();
+        }
+        results.reverse();
+
+        zinv.clear();
+        kinv.clear();
+        for z in zprod.iter_mut() {
+            z.clear();
         }
-        if sigs.is_high() {
-            sigs = -sigs;
-            recid ^= 1;
+        for k in kprod.iter_mut() {
+            k.clear();
         }
-        Ok((sigr, sigs, recid))
+        results
     }
 }
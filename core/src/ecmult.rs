@@ -1,23 +1,85 @@
+#[cfg(feature = "alloc")]
+use crate::group::set_table_gej_var;
 use crate::{
+    error::{Error, ErrorDetail, ErrorKind, Input},
     field::Field,
-    group::{globalz_set_table_gej, set_table_gej_var, Affine, AffineStorage, Jacobian, AFFINE_G},
+    group::{globalz_set_table_gej, Affine, AffineStorage, Jacobian, Projective, AFFINE_G},
     scalar::Scalar,
 };
+#[cfg(feature = "alloc")]
 use alloc::{
-    alloc::{alloc, Layout},
+    alloc::{alloc, handle_alloc_error, Layout},
     boxed::Box,
     vec,
     vec::Vec,
 };
-use subtle::Choice;
+use core::mem::MaybeUninit;
+#[cfg(feature = "hmac")]
+use hmac_drbg::HmacDRBG;
+#[cfg(feature = "hmac")]
+use sha2::Sha256;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
 
 pub const WINDOW_A: usize = 5;
+/// Window size of the precomputed `G` table in `ECMultContext`. The table
+/// holds `1 << (WINDOW_G - 2)` entries of 64 bytes each. Selected by the
+/// `ecmult-window-small` (4 KiB) and `ecmult-window-large` (4 MiB) features,
+/// defaulting to 1 MiB. If both features are enabled, the small table wins.
+#[cfg(feature = "ecmult-window-small")]
+pub const WINDOW_G: usize = 8;
+#[cfg(all(feature = "ecmult-window-large", not(feature = "ecmult-window-small")))]
+pub const WINDOW_G: usize = 18;
+#[cfg(not(any(feature = "ecmult-window-small", feature = "ecmult-window-large")))]
 pub const WINDOW_G: usize = 16;
 pub const ECMULT_TABLE_SIZE_A: usize = 1 << (WINDOW_A - 2);
 pub const ECMULT_TABLE_SIZE_G: usize = 1 << (WINDOW_G - 2);
+/// Number of entries of each part of the `G` table computed by
+/// `ECMultContext::table_part_const`.
+pub const ECMULT_TABLE_PART_SIZE: usize = if ECMULT_TABLE_SIZE_G < 256 {
+    ECMULT_TABLE_SIZE_G
+} else {
+    256
+};
+/// Number of parts of the `G` table.
+pub const ECMULT_TABLE_PARTS: usize = ECMULT_TABLE_SIZE_G / ECMULT_TABLE_PART_SIZE;
 pub const WNAF_BITS: usize = 256;
 
-fn odd_multiples_table_storage_var(pre: &mut [AffineStorage], a: &Jacobian) {
+/// Number of blocks in the signed-digit multi-comb used by
+/// `ECMultGenContext`. Each block has its own table of
+/// `ECMULT_GEN_COMB_POINTS` entries. Selected together with
+/// `ECMULT_GEN_COMB_TEETH` by the `ecmult-gen-small` (2 KiB) and
+/// `ecmult-gen-large` (86 KiB) features, defaulting to 22 KiB. If both
+/// features are enabled, the small table wins.
+#[cfg(feature = "ecmult-gen-small")]
+pub const ECMULT_GEN_COMB_BLOCKS: usize = 2;
+#[cfg(all(feature = "ecmult-gen-large", not(feature = "ecmult-gen-small")))]
+pub const ECMULT_GEN_COMB_BLOCKS: usize = 43;
+#[cfg(not(any(feature = "ecmult-gen-small", feature = "ecmult-gen-large")))]
+pub const ECMULT_GEN_COMB_BLOCKS: usize = 11;
+/// Number of teeth in each comb block, i.e. the number of scalar bits
+/// gathered for a single table lookup.
+#[cfg(feature = "ecmult-gen-small")]
+pub const ECMULT_GEN_COMB_TEETH: usize = 5;
+#[cfg(all(feature = "ecmult-gen-large", not(feature = "ecmult-gen-small")))]
+pub const ECMULT_GEN_COMB_TEETH: usize = 6;
+#[cfg(not(any(feature = "ecmult-gen-small", feature = "ecmult-gen-large")))]
+pub const ECMULT_GEN_COMB_TEETH: usize = 6;
+/// Distance in bits between two consecutive teeth, which is also the
+/// number of doublings performed by `ecmult_gen`.
+pub const ECMULT_GEN_COMB_SPACING: usize =
+    256usize.div_ceil(ECMULT_GEN_COMB_BLOCKS * ECMULT_GEN_COMB_TEETH);
+/// Number of scalar bits covered by the comb. This is at least 256.
+pub const ECMULT_GEN_COMB_BITS: usize =
+    ECMULT_GEN_COMB_BLOCKS * ECMULT_GEN_COMB_TEETH * ECMULT_GEN_COMB_SPACING;
+/// Number of table entries per comb block.
+pub const ECMULT_GEN_COMB_POINTS: usize = 1 << (ECMULT_GEN_COMB_TEETH - 1);
+
+/// Compute the odd multiples `a, 3a, ..., (2*pre.len() - 1)a` in affine
+/// storage form. A table of `1 << (w - 2)` entries can be passed to
+/// `ECMultContext::ecmult_prepared` with window `w`.
+#[cfg(feature = "alloc")]
+pub fn odd_multiples_table_storage_var(pre: &mut [AffineStorage], a: &Jacobian) {
     let mut prej: Vec<Jacobian> = Vec::with_capacity(pre.len());
     for _ in 0..pre.len() {
         prej.push(Jacobian::default());
@@ -39,7 +101,204 @@ fn odd_multiples_table_storage_var(pre: &mut [AffineStorage], a: &Jacobian) {
     }
 }
 
+/// `G/2`, the starting point of the multi-comb table.
+const AFFINE_G_HALF: Affine = Affine::new(
+    Field::new(
+        0x00000000, 0x00000000, 0x0000003B, 0x78CE563F, 0x89A0ED94, 0x14F5AA28, 0xAD0D96D6,
+        0x795F9C63,
+    ),
+    Field::new(
+        0xC0C68640, 0x8D517DFD, 0x67C23676, 0x51380D00, 0xD126E422, 0x9631FD03, 0xF8FF35EE,
+        0xF1A61E3C,
+    ),
+);
+
+/// Number of points brought to affine coordinates with a single field
+/// inversion when computing tables in const context.
+const CONST_BATCH_SIZE: usize = 64;
+
+/// Convert the finite points `a` to affine storage form in `r`, sharing a
+/// single field inversion. At most `CONST_BATCH_SIZE` points are allowed.
+const fn set_all_proj_storage_const(r: &mut [AffineStorage], a: &[Projective]) {
+    assert!(r.len() == a.len() && a.len() <= CONST_BATCH_SIZE);
+    if a.is_empty() {
+        return;
+    }
+
+    let mut prod = [Field::ZERO; CONST_BATCH_SIZE];
+    prod[0] = a[0].z;
+    let mut i = 1;
+    while i < a.len() {
+        prod[i] = prod[i - 1].mul_const(&a[i].z);
+        i += 1;
+    }
+
+    let mut inv = prod[a.len() - 1].inv_const();
+    let mut i = a.len();
+    while i > 0 {
+        i -= 1;
+        let mut zi = inv;
+        if i > 0 {
+            zi = zi.mul_const(&prod[i - 1]);
+            inv = inv.mul_const(&a[i].z);
+        }
+        let ge = Affine::new(a[i].x.mul_const(&zi), a[i].y.mul_const(&zi));
+        r[i] = ge.to_storage();
+    }
+}
+
+/// Same as `odd_multiples_table_storage_var`, usable in const context.
+pub const fn odd_multiples_table_storage_const(pre: &mut [AffineStorage], a: &Affine) {
+    let p = projective_const(a);
+    let d = p.double();
+    odd_multiples_from_const(pre, p, &d);
+}
+
+const fn projective_const(a: &Affine) -> Projective {
+    let mut p = Projective {
+        x: a.x,
+        y: a.y,
+        z: Field::from_int(1),
+    };
+    p.x.normalize_weak();
+    p.y.normalize_weak();
+    p
+}
+
+/// `n * a`, by double-and-add. Not constant time, for public `n` only.
+const fn mul_int_const(a: &Projective, n: usize) -> Projective {
+    let mut r = Projective::INFINITY;
+    let mut bit = usize::BITS - n.leading_zeros();
+    while bit > 0 {
+        bit -= 1;
+        r = r.double();
+        if (n >> bit) & 1 == 1 {
+            r = r.add(a);
+        }
+    }
+    r
+}
+
+/// Fill `pre` with `p, p + d, p + 2d, ...` in affine storage form.
+const fn odd_multiples_from_const(pre: &mut [AffineStorage], mut p: Projective, d: &Projective) {
+    let mut batch = [Projective::INFINITY; CONST_BATCH_SIZE];
+    let mut start = 0;
+    while start < pre.len() {
+        let n = if pre.len() - start < CONST_BATCH_SIZE {
+            pre.len() - start
+        } else {
+            CONST_BATCH_SIZE
+        };
+        let mut i = 0;
+        while i < n {
+            batch[i] = p;
+            p = p.add(d);
+            i += 1;
+        }
+
+        let (_, rest) = pre.split_at_mut(start);
+        let (chunk, _) = rest.split_at_mut(n);
+        let (points, _) = batch.split_at(n);
+        set_all_proj_storage_const(chunk, points);
+        start += n;
+    }
+}
+
+// Each comb block is converted to affine coordinates as a single batch.
+const _: () = assert!(ECMULT_GEN_COMB_POINTS <= CONST_BATCH_SIZE);
+
+/// Same as `ecmult_gen_compute_table` for the configured comb, usable in
+/// const context and without an allocator.
+const fn ecmult_gen_fill_table_const(
+    table: &mut [[AffineStorage; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS],
+) {
+    let mut u = projective_const(&AFFINE_G_HALF);
+    let mut block = 0;
+    while block < ECMULT_GEN_COMB_BLOCKS {
+        ecmult_gen_block_const(&mut table[block], &mut u);
+        block += 1;
+    }
+}
+
+/// Compute the comb block starting at `u`, and advance `u` to the start of
+/// the next block.
+const fn ecmult_gen_block_const(
+    block: &mut [AffineStorage; ECMULT_GEN_COMB_POINTS],
+    u: &mut Projective,
+) {
+    let mut sum = Projective::INFINITY;
+    let mut ds = [Projective::INFINITY; ECMULT_GEN_COMB_TEETH];
+    let mut tooth = 0;
+    while tooth < ECMULT_GEN_COMB_TEETH {
+        sum = sum.add(u);
+        *u = u.double();
+        ds[tooth] = *u;
+        let mut i = 1;
+        while i < ECMULT_GEN_COMB_SPACING {
+            *u = u.double();
+            i += 1;
+        }
+        tooth += 1;
+    }
+
+    let mut vs = [Projective::INFINITY; ECMULT_GEN_COMB_POINTS];
+    vs[0] = sum.neg();
+    let mut pos = 1;
+    let mut tooth = 0;
+    while tooth < ECMULT_GEN_COMB_TEETH - 1 {
+        let stride = 1 << tooth;
+        let mut i = 0;
+        while i < stride {
+            vs[pos] = vs[pos - stride].add(&ds[tooth]);
+            pos += 1;
+            i += 1;
+        }
+        tooth += 1;
+    }
+
+    set_all_proj_storage_const(block, &vs);
+}
+
+// The static contexts are shared between threads, see the thread safety
+// notes of both context types.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ECMultContext>();
+    assert_send_sync::<ECMultGenContext>();
+};
+
+/// Zero-initialize `out` and return a reference to it. The all-zero bit
+/// pattern is a valid value of both context types.
+///
+/// # Safety
+/// `T` must be valid when all its bytes are zero.
+unsafe fn zeroed_in_place<T>(out: &mut MaybeUninit<T>) -> &mut T {
+    out.as_mut_ptr().write_bytes(0, 1);
+    &mut *out.as_mut_ptr()
+}
+
+/// Overwrite `value` with `fresh`, with a volatile write that the compiler
+/// does not remove. Used for the `HmacDRBG` and hasher states, which do not
+/// implement `Zeroize`. This is a best-effort overwrite, not a zeroization:
+/// padding bytes and copies left by earlier moves are not cleared. Types
+/// with drop glue are assigned normally instead, so that they are dropped.
+pub(crate) fn wipe<T>(value: &mut T, fresh: T) {
+    if core::mem::needs_drop::<T>() {
+        *value = fresh;
+    } else {
+        // Safety: `value` is valid for writes, and the old value needs no
+        // drop, so overwriting it leaks nothing.
+        unsafe { core::ptr::write_volatile(value, fresh) }
+    }
+}
+
 /// Context for accelerating the computation of a*P + b*G.
+///
+/// # Thread safety
+/// The context is `Send` and `Sync`. Its table is never modified after
+/// construction, and every operation takes `&self`, so a single context,
+/// such as the static `ECMULT_CONTEXT`, can be used from any number of
+/// threads at once without locking.
 pub struct ECMultContext {
     pre_g: [AffineStorage; ECMULT_TABLE_SIZE_G],
 }
@@ -55,12 +314,70 @@ impl ECMultContext {
         Self { pre_g }
     }
 
+    /// Generate a new `ECMultContext` in const context, such as the
+    /// initializer of a `static`. The table is the same as the one of
+    /// `ECMultContext::new_boxed`. Note that evaluating this at compile
+    /// time is slow, and exceeds the compiler's limit on the work done for
+    /// a single constant unless `WINDOW_G` is small. See
+    /// `ECMultContext::table_part_const`.
+    pub const fn new_const() -> Self {
+        let mut pre_g = [AffineStorage::ZERO; ECMULT_TABLE_SIZE_G];
+        odd_multiples_table_storage_const(&mut pre_g, &AFFINE_G);
+        Self { pre_g }
+    }
+
+    /// Compute part `k` of the table, its entries from
+    /// `k * ECMULT_TABLE_PART_SIZE`, in const context. Each part stays
+    /// within the compiler's limit on the work done for a single constant,
+    /// so a table evaluated at compile time can be built from parts that
+    /// are each computed in their own constant, then put in place with
+    /// `ECMultContext::set_table_part_const`. The part is empty if `k` is
+    /// at least `ECMULT_TABLE_PARTS`.
+    pub const fn table_part_const(k: usize) -> [AffineStorage; ECMULT_TABLE_PART_SIZE] {
+        let mut part = [AffineStorage::ZERO; ECMULT_TABLE_PART_SIZE];
+        if k < ECMULT_TABLE_PARTS {
+            let g = projective_const(&AFFINE_G);
+            let start = mul_int_const(&g, 2 * k * ECMULT_TABLE_PART_SIZE + 1);
+            odd_multiples_from_const(&mut part, start, &g.double());
+        }
+        part
+    }
+
+    /// Put part `k` of the table, computed by
+    /// `ECMultContext::table_part_const`, in place. Parts past
+    /// `ECMULT_TABLE_PARTS` are ignored.
+    pub const fn set_table_part_const(
+        &mut self,
+        k: usize,
+        part: &[AffineStorage; ECMULT_TABLE_PART_SIZE],
+    ) {
+        if k >= ECMULT_TABLE_PARTS {
+            return;
+        }
+        let mut i = 0;
+        while i < ECMULT_TABLE_PART_SIZE {
+            self.pre_g[k * ECMULT_TABLE_PART_SIZE + i] = part[i];
+            i += 1;
+        }
+    }
+
     /// Inspect raw values of `ECMultContext`.
     pub fn inspect_raw(&self) -> &[AffineStorage; ECMULT_TABLE_SIZE_G] {
         &self.pre_g
     }
 
+    /// Generate a new `ECMultContext` in caller-provided storage, without
+    /// allocating and without building the context on the stack. Note that
+    /// this function is expensive.
+    pub fn new_in_place(out: &mut MaybeUninit<Self>) -> &mut Self {
+        // The table is fully overwritten below.
+        let this = unsafe { zeroed_in_place(out) };
+        odd_multiples_table_storage_const(&mut this.pre_g, &AFFINE_G);
+        this
+    }
+
     /// Generate a new `ECMultContext` on the heap. Note that this function is expensive.
+    #[cfg(feature = "alloc")]
     pub fn new_boxed() -> Box<Self> {
         // This unsafe block allocates a new, unitialized `ECMultContext` and
         // then fills in the value. This is to avoid allocating it on stack
@@ -87,82 +404,197 @@ impl ECMultContext {
 
 /// Set a batch of group elements equal to the inputs given in jacobian
 /// coordinates. Not constant time.
+#[cfg(feature = "alloc")]
 pub fn set_all_gej_var(a: &[Jacobian]) -> Vec<Affine> {
-    let mut az: Vec<Field> = Vec::with_capacity(a.len());
-    for point in a {
-        if !point.is_infinity() {
-            az.push(point.z);
-        }
-    }
-    let azi: Vec<Field> = inv_all_var(&az);
-
     let mut ret = vec![Affine::default(); a.len()];
+    set_all_gej_var_to(&mut ret, a);
+    ret
+}
+
+/// Same as `set_all_gej_var`, writing the results to `r`, which must have
+/// the same length as `a`. Does not allocate. Not constant time.
+pub fn set_all_gej_var_to(r: &mut [Affine], a: &[Jacobian]) {
+    assert!(r.len() == a.len());
 
-    let mut count = 0;
+    // Store the product of the z coordinates of all previous finite points
+    // in the x coordinate of each finite output.
+    let mut acc = Field::from_int(1);
+    let mut any_finite = false;
     for i in 0..a.len() {
-        ret[i].infinity = a[i].infinity;
+        if a[i].is_infinity() {
+            r[i] = Affine::default();
+            r[i].infinity = true;
+        } else {
+            r[i].x = acc;
+            acc *= a[i].z;
+            any_finite = true;
+        }
+    }
+    if !any_finite {
+        return;
+    }
+
+    let mut u = acc.inv_var();
+    for i in (0..a.len()).rev() {
         if !a[i].is_infinity() {
-            ret[i].set_gej_zinv(&a[i], &azi[count]);
-            count += 1;
+            let zi = r[i].x * u;
+            u *= a[i].z;
+            r[i].set_gej_zinv(&a[i], &zi);
         }
     }
-    ret
 }
 
 /// Calculate the (modular) inverses of a batch of field
 /// elements. Requires the inputs' magnitudes to be at most 8. The
 /// output magnitudes are 1 (but not guaranteed to be
 /// normalized).
+#[cfg(feature = "alloc")]
 pub fn inv_all_var(fields: &[Field]) -> Vec<Field> {
+    let mut ret = vec![Field::default(); fields.len()];
+    inv_all_var_to(&mut ret, fields);
+    ret
+}
+
+/// Same as `inv_all_var`, writing the results to `r`, which must have the
+/// same length as `fields`. Does not allocate.
+pub fn inv_all_var_to(r: &mut [Field], fields: &[Field]) {
+    assert!(r.len() == fields.len());
     if fields.is_empty() {
-        return Vec::new();
+        return;
     }
 
-    let mut ret = Vec::with_capacity(fields.len());
-    ret.push(fields[0]);
-
+    r[0] = fields[0];
     for i in 1..fields.len() {
-        ret.push(Field::default());
-        ret[i] = ret[i - 1] * fields[i];
+        r[i] = r[i - 1] * fields[i];
     }
 
-    let mut u = ret[fields.len() - 1].inv_var();
+    let mut u = r[fields.len() - 1].inv_var();
 
     for i in (1..fields.len()).rev() {
         let j = i;
         let i = i - 1;
-        ret[j] = ret[i] * u;
+        r[j] = r[i] * u;
         u *= fields[j];
     }
 
-    ret[0] = u;
-    ret
+    r[0] = u;
 }
 
 const GEN_BLIND: Scalar = Scalar([
     2217680822, 850875797, 1046150361, 1330484644, 4015777837, 2466086288, 2052467175, 2084507480,
 ]);
-const GEN_INITIAL: Jacobian = Jacobian {
-    x: Field::new_raw(
-        586608, 43357028, 207667908, 262670128, 142222828, 38529388, 267186148, 45417712,
-        115291924, 13447464,
+/// `-GEN_BLIND*G`.
+const GEN_GE_OFFSET: Affine = Affine::new(
+    Field::new(
+        0x079FBCA1, 0xDA9BE26C, 0x041739A3, 0x5CCE4B4C, 0xA0E3E205, 0x72AA5F56, 0x174C5C3C,
+        0xBC98C655,
     ),
-    y: Field::new_raw(
-        12696548, 208302564, 112025180, 191752716, 143238548, 145482948, 228906000, 69755164,
-        243572800, 210897016,
+    Field::new(
+        0x5FA78C83, 0xA5C57027, 0x24DEE783, 0x49B72D54, 0x634AD3D2, 0x0FEE2F14, 0x0BAE367E,
+        0xAB80B316,
     ),
-    z: Field::new_raw(
-        3685368, 75404844, 20246216, 5748944, 73206666, 107661790, 110806176, 73488774, 5707384,
-        104448710,
-    ),
-    infinity: false,
-};
+);
+const GEN_PROJ_BLIND: Field = Field::new(
+    0xE0EFE47E, 0x962C4D3B, 0xB2057B8D, 0x11742E2E, 0x6AC9DFA6, 0xB1284615, 0x98615C59, 0xE639C2C6,
+);
+/// `(2^ECMULT_GEN_COMB_BITS - 1)/2 mod n`. Adding it to a scalar turns its
+/// bits into the signed digits used by the comb, see `ecmult_gen`.
+#[cfg(feature = "ecmult-gen-small")]
+const GEN_SCALAR_DIFF: Scalar = Scalar([
+    0xE6691698, 0xE1563ADF, 0xDD5F4E3F, 0x87E0873D, 0x00000009, 0x00000000, 0x00000000, 0x80000000,
+]);
+#[cfg(all(feature = "ecmult-gen-large", not(feature = "ecmult-gen-small")))]
+const GEN_SCALAR_DIFF: Scalar = Scalar([
+    0xC7AE9E1E, 0x6044722C, 0xF9130FA6, 0xE7F9B4A5, 0x00000001, 0x00000000, 0x00000000, 0x80000000,
+]);
+#[cfg(not(any(feature = "ecmult-gen-small", feature = "ecmult-gen-large")))]
+const GEN_SCALAR_DIFF: Scalar = Scalar([
+    0x4CFA8020, 0xF6B9E8DE, 0xB354323D, 0x05E8FB1B, 0x000000A2, 0x00000000, 0x00000000, 0x80000000,
+]);
 
-/// Context for accelerating the computation of a*G.
+/// Compute the signed-digit multi-comb table for the generator with the
+/// given number of `blocks` and `teeth`, and `spacing` between teeth.
+/// `blocks * teeth * spacing` must be at least 256. The entry for block `b`
+/// and index `i` is written to `table[b * (1 << (teeth - 1)) + i]`, and
+/// equals `sum((2*bit(i, k) - 1) * 2^((b*teeth + k)*spacing) * G/2)` over
+/// all teeth `k`, where `bit(i, teeth - 1)` is taken to be 1.
+#[cfg(feature = "alloc")]
+pub fn ecmult_gen_compute_table(
+    table: &mut [AffineStorage],
+    blocks: usize,
+    teeth: usize,
+    spacing: usize,
+) {
+    let points = 1 << (teeth - 1);
+    assert!((1..=8).contains(&teeth));
+    assert!(blocks * teeth * spacing >= 256);
+    assert!(table.len() == blocks * points);
+
+    // Compute G/2.
+    let half = Scalar::from_int(2).inv();
+    let mut u = Jacobian::default();
+    u.set_infinity();
+    for i in (0..256).rev() {
+        u = u.double_var(None);
+        if half.bits(i, 1) == 1 {
+            u = u.add_ge_var(&AFFINE_G, None);
+        }
+    }
+
+    // For each block, compute the sum of the multiples of G/2 at its teeth,
+    // negated, in `vs[block * points]`, and twice each of these multiples
+    // in `ds`.
+    let mut ds: Vec<Jacobian> = vec![Jacobian::default(); teeth];
+    let mut vs: Vec<Jacobian> = vec![Jacobian::default(); blocks * points];
+    let mut vs_pos = 0;
+    for block in 0..blocks {
+        let mut sum = Jacobian::default();
+        sum.set_infinity();
+        for tooth in 0..teeth {
+            sum = sum.add_var(&u, None);
+            u = u.double_var(None);
+            ds[tooth] = u;
+            // Skip the remaining doublings after the very last tooth.
+            if block + tooth != blocks + teeth - 2 {
+                for _ in 1..spacing {
+                    u = u.double_var(None);
+                }
+            }
+        }
+        vs[vs_pos] = sum.neg();
+        vs_pos += 1;
+        // Every other entry is obtained by flipping the sign of some teeth,
+        // i.e. by adding twice the corresponding multiples.
+        for tooth in 0..(teeth - 1) {
+            let stride = 1 << tooth;
+            for _ in 0..stride {
+                vs[vs_pos] = vs[vs_pos - stride].add_var(&ds[tooth], None);
+                vs_pos += 1;
+            }
+        }
+    }
+    debug_assert!(vs_pos == blocks * points);
+
+    let prec = set_all_gej_var(&vs);
+    for (entry, p) in table.iter_mut().zip(prec.iter()) {
+        *entry = (*p).into();
+    }
+}
+
+/// Context for accelerating the computation of a*G, using the signed-digit
+/// multi-comb method.
+///
+/// # Thread safety
+/// The context is `Send` and `Sync`. Signing and key generation take
+/// `&self`, so a single context, such as the static `ECMULT_GEN_CONTEXT`,
+/// can be used from any number of threads at once without locking. Only
+/// `randomize` takes `&mut self`; to re-blind a context that is shared,
+/// use `randomized_boxed` to get a blinded copy instead.
 pub struct ECMultGenContext {
-    prec: [[AffineStorage; 16]; 64],
+    prec: [[AffineStorage; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS],
     blind: Scalar,
-    initial: Jacobian,
+    ge_offset: Affine,
+    proj_blind: Field,
 }
 
 impl ECMultGenContext {
@@ -172,20 +604,94 @@ impl ECMultGenContext {
     /// The function is unsafe because incorrect value of `pre_g` can lead to
     /// crypto logic failure. You most likely do not want to use this function,
     /// but `ECMultGenContext::new_boxed`.
-    pub const unsafe fn new_from_raw(prec: [[AffineStorage; 16]; 64]) -> Self {
+    pub const unsafe fn new_from_raw(
+        prec: [[AffineStorage; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS],
+    ) -> Self {
         Self {
             prec,
             blind: GEN_BLIND,
-            initial: GEN_INITIAL,
+            ge_offset: GEN_GE_OFFSET,
+            proj_blind: GEN_PROJ_BLIND,
+        }
+    }
+
+    /// Generate a new `ECMultGenContext` in const context, such as the
+    /// initializer of a `static`. The table is the same as the one of
+    /// `ECMultGenContext::new_boxed`.
+    pub const fn new_const() -> Self {
+        let mut prec = [[AffineStorage::ZERO; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS];
+        ecmult_gen_fill_table_const(&mut prec);
+        Self {
+            prec,
+            blind: GEN_BLIND,
+            ge_offset: GEN_GE_OFFSET,
+            proj_blind: GEN_PROJ_BLIND,
+        }
+    }
+
+    /// Compute block `block` of the comb table in const context, so that
+    /// each block can be computed in its own constant. See
+    /// `ECMultContext::table_part_const`. The block is empty if `block` is
+    /// at least `ECMULT_GEN_COMB_BLOCKS`.
+    pub const fn table_block_const(block: usize) -> [AffineStorage; ECMULT_GEN_COMB_POINTS] {
+        let mut ret = [AffineStorage::ZERO; ECMULT_GEN_COMB_POINTS];
+        if block < ECMULT_GEN_COMB_BLOCKS {
+            let mut u = projective_const(&AFFINE_G_HALF);
+            let mut i = 0;
+            while i < block * ECMULT_GEN_COMB_TEETH * ECMULT_GEN_COMB_SPACING {
+                u = u.double();
+                i += 1;
+            }
+            ecmult_gen_block_const(&mut ret, &mut u);
+        }
+        ret
+    }
+
+    /// Put block `block` of the comb table, computed by
+    /// `ECMultGenContext::table_block_const`, in place. Blocks past
+    /// `ECMULT_GEN_COMB_BLOCKS` are ignored.
+    pub const fn set_table_block_const(
+        &mut self,
+        block: usize,
+        entries: &[AffineStorage; ECMULT_GEN_COMB_POINTS],
+    ) {
+        if block < ECMULT_GEN_COMB_BLOCKS {
+            self.prec[block] = *entries;
         }
     }
 
     /// Inspect `ECMultGenContext` values.
-    pub fn inspect_raw(&self) -> &[[AffineStorage; 16]; 64] {
+    pub fn inspect_raw(
+        &self,
+    ) -> &[[AffineStorage; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS] {
         &self.prec
     }
 
+    /// Generate a new `ECMultGenContext` in caller-provided storage, without
+    /// allocating and without building the context on the stack. The
+    /// context starts out with the default blinding. Note that this
+    /// function is expensive.
+    pub fn new_in_place(out: &mut MaybeUninit<Self>) -> &mut Self {
+        // Every field is overwritten below.
+        let this = unsafe { zeroed_in_place(out) };
+        ecmult_gen_fill_table_const(&mut this.prec);
+        this.blind = GEN_BLIND;
+        this.ge_offset = GEN_GE_OFFSET;
+        this.proj_blind = GEN_PROJ_BLIND;
+        this
+    }
+
+    /// Copy the context, including its current blinding, into
+    /// caller-provided storage.
+    pub fn clone_in_place<'a>(&self, out: &'a mut MaybeUninit<Self>) -> &'a mut Self {
+        unsafe {
+            core::ptr::copy_nonoverlapping(self, out.as_mut_ptr(), 1);
+            &mut *out.as_mut_ptr()
+        }
+    }
+
     /// Generate a new `ECMultGenContext` on the heap. Note that this function is expensive.
+    #[cfg(feature = "alloc")]
     pub fn new_boxed() -> Box<Self> {
         // This unsafe block allocates a new, unitialized `ECMultGenContext` and
         // then fills in the value. This is to avoid allocating it on stack
@@ -195,65 +701,109 @@ impl ECMultGenContext {
             let ptr = alloc(Layout::new::<ECMultGenContext>()) as *mut ECMultGenContext;
             let mut this = Box::from_raw(ptr);
 
-            for j in 0..64 {
-                for i in 0..16 {
+            for j in 0..ECMULT_GEN_COMB_BLOCKS {
+                for i in 0..ECMULT_GEN_COMB_POINTS {
                     this.prec[j][i] = AffineStorage::default();
                 }
             }
 
             this.blind = GEN_BLIND;
-            this.initial = GEN_INITIAL;
+            this.ge_offset = GEN_GE_OFFSET;
+            this.proj_blind = GEN_PROJ_BLIND;
 
             this
         };
 
-        let mut gj = Jacobian::default();
-        gj.set_ge(&AFFINE_G);
-
-        // Construct a group element with no known corresponding scalar (nothing up my sleeve).
-        let mut nums_32 = [0u8; 32];
-        debug_assert!(b"The scalar for this x is unknown".len() == 32);
-        for (i, v) in b"The scalar for this x is unknown".iter().enumerate() {
-            nums_32[i] = *v;
-        }
-        let mut nums_x = Field::default();
-        assert!(nums_x.set_b32(&nums_32));
-        let mut nums_ge = Affine::default();
-        assert!(nums_ge.set_xo_var(&nums_x, false));
-        let mut nums_gej = Jacobian::default();
-        nums_gej.set_ge(&nums_ge);
-        nums_gej = nums_gej.add_ge_var(&AFFINE_G, None);
-
-        // Compute prec.
-        let mut precj: Vec<Jacobian> = Vec::with_capacity(1024);
-        for _ in 0..1024 {
-            precj.push(Jacobian::default());
-        }
-        let mut gbase = gj;
-        let mut numsbase = nums_gej;
-        for j in 0..64 {
-            precj[j * 16] = numsbase;
-            for i in 1..16 {
-                precj[j * 16 + i] = precj[j * 16 + i - 1].add_var(&gbase, None);
+        let mut prec =
+            vec![AffineStorage::default(); ECMULT_GEN_COMB_BLOCKS * ECMULT_GEN_COMB_POINTS];
+        ecmult_gen_compute_table(
+            &mut prec,
+            ECMULT_GEN_COMB_BLOCKS,
+            ECMULT_GEN_COMB_TEETH,
+            ECMULT_GEN_COMB_SPACING,
+        );
+
+        for j in 0..ECMULT_GEN_COMB_BLOCKS {
+            for i in 0..ECMULT_GEN_COMB_POINTS {
+                this.prec[j][i] = prec[j * ECMULT_GEN_COMB_POINTS + i];
             }
-            for _ in 0..4 {
-                gbase = gbase.double_var(None);
-            }
-            numsbase = numsbase.double_var(None);
-            if j == 62 {
-                numsbase = numsbase.neg();
-                numsbase = numsbase.add_var(&nums_gej, None);
+        }
+
+        this
+    }
+
+    /// Copy the context, including its current blinding, onto the heap.
+    /// This is much cheaper than `ECMultGenContext::new_boxed`.
+    #[cfg(feature = "alloc")]
+    pub fn clone_boxed(&self) -> Box<Self> {
+        // Copy through a raw allocation for the same reason as in `new_boxed`.
+        unsafe {
+            let layout = Layout::new::<ECMultGenContext>();
+            let ptr = alloc(layout) as *mut ECMultGenContext;
+            if ptr.is_null() {
+                handle_alloc_error(layout);
             }
+            core::ptr::copy_nonoverlapping(self, ptr, 1);
+            Box::from_raw(ptr)
         }
-        let prec = set_all_gej_var(&precj);
+    }
 
-        for j in 0..64 {
-            for i in 0..16 {
-                let pg: AffineStorage = prec[j * 16 + i].into();
-                this.prec[j][i] = pg;
+    /// Re-blind the context using a 32-byte random `seed`.
+    ///
+    /// `ecmult_gen` computes `ge_offset + (gn + blind)*G`, where `ge_offset`
+    /// is `-blind*G`. By default every context shares the same `blind`, so
+    /// this function derives a fresh blinding scalar and a matching offset
+    /// point, and also a fresh factor for the projective coordinates used
+    /// during the computation.
+    /// The previous blinding value is chained into the derivation, so a weak
+    /// seed never makes the blinding weaker than it already was. This is the
+    /// equivalent of `secp256k1_context_randomize` in the C library. The
+    /// results of all operations using the context are unchanged.
+    #[cfg(feature = "hmac")]
+    pub fn randomize(&mut self, seed: &[u8; 32]) {
+        let mut blind_b32 = self.blind.b32();
+        let mut drbg = HmacDRBG::<Sha256>::new(seed, &blind_b32, &[]);
+        let mut generated = [0u8; 32];
+
+        // Randomize the projection to defend against multiplier sidechannels.
+        let mut s = Field::default();
+        loop {
+            drbg.generate_to_slice(&mut generated, None);
+            if s.set_b32(&generated) && !s.is_zero() {
+                break;
+            }
+        }
+        self.proj_blind = s;
+
+        let mut b = Scalar::default();
+        loop {
+            drbg.generate_to_slice(&mut generated, None);
+            let overflow = bool::from(b.set_b32(&generated));
+            if !overflow && !b.is_zero() {
+                break;
             }
         }
 
+        let mut gb = Jacobian::default();
+        self.ecmult_gen(&mut gb, &b);
+        self.blind = -b;
+        self.ge_offset.set_gej(&gb);
+
+        b.clear();
+        s.clear();
+        gb.clear();
+        blind_b32.zeroize();
+        generated.zeroize();
+        wipe(&mut drbg, HmacDRBG::new(&[], &[], &[]));
+    }
+
+    /// Copy the context onto the heap and re-blind the copy using `seed`.
+    /// This is the way to get a randomized context out of a shared one,
+    /// such as the static `ECMULT_GEN_CONTEXT`.
+    #[cfg(all(feature = "hmac", feature = "alloc"))]
+    pub fn randomized_boxed(&self, seed: &[u8; 32]) -> Box<Self> {
+        let mut this = self.clone_boxed();
+        this.randomize(seed);
         this
     }
 }
@@ -316,13 +866,13 @@ fn table_get_ge_const(r: &mut Affine, pre: &[Affine], n: i32, w: usize) {
     debug_assert!(n >= -((1 << (w - 1)) - 1));
     debug_assert!(n <= ((1 << (w - 1)) - 1));
     for m in 0..pre.len() {
-        let flag = m == idx_n as usize;
+        let flag = (m as u32).ct_eq(&(idx_n as u32));
         r.x.cmov(&pre[m].x, flag);
         r.y.cmov(&pre[m].y, flag);
     }
     r.infinity = false;
     let neg_y = r.y.neg(1);
-    r.y.cmov(&neg_y, n != abs_n);
+    r.y.cmov(&neg_y, !n.ct_eq(&abs_n));
 }
 
 fn table_get_ge_storage(r: &mut Affine, pre: &[AffineStorage], n: i32, w: usize) {
@@ -488,6 +1038,44 @@ impl ECMultContext {
         }
     }
 
+    /// Compute `na*A + ng*G`, where `pre_a` holds the odd multiples of `A`
+    /// for window `w_a`, as computed by `odd_multiples_table_storage_var`.
+    /// This skips building the table for `A`, and allows a window larger
+    /// than `WINDOW_A` when the same `A` is used many times.
+    pub fn ecmult_prepared(
+        &self,
+        r: &mut Jacobian,
+        pre_a: &[AffineStorage],
+        w_a: usize,
+        na: &Scalar,
+        ng: &Scalar,
+    ) {
+        debug_assert!(pre_a.len() == 1 << (w_a - 2));
+        let mut tmpa = Affine::default();
+        let mut wnaf_na = [0i32; 256];
+        let mut wnaf_ng = [0i32; 256];
+        let bits_na = ecmult_wnaf(&mut wnaf_na, na, w_a);
+        let bits_ng = ecmult_wnaf(&mut wnaf_ng, ng, WINDOW_G);
+        let bits = core::cmp::max(bits_na, bits_ng);
+
+        r.set_infinity();
+        for i in (0..bits).rev() {
+            let mut n;
+            *r = r.double_var(None);
+
+            n = wnaf_na[i as usize];
+            if i < bits_na && n != 0 {
+                table_get_ge_storage(&mut tmpa, pre_a, n, w_a);
+                *r = r.add_ge_var(&tmpa, None);
+            }
+            n = wnaf_ng[i as usize];
+            if i < bits_ng && n != 0 {
+                table_get_ge_storage(&mut tmpa, &self.pre_g, n, WINDOW_G);
+                *r = r.add_ge_var(&tmpa, None);
+            }
+        }
+    }
+
     pub fn ecmult_const(&self, r: &mut Jacobian, a: &Affine, scalar: &Scalar) {
         const WNAF_SIZE: usize = (WNAF_BITS + (WINDOW_A - 1) - 1) / (WINDOW_A - 1);
 
@@ -547,7 +1135,7 @@ impl ECMultContext {
         a2_stor = correction.into();
 
         /* For odd numbers this is 2a (so replace it), for even ones a (so no-op) */
-        correction_1_stor.cmov(&a2_stor, skew_1 == 2);
+        correction_1_stor.cmov(&a2_stor, skew_1.ct_eq(&2));
 
         /* Apply the correction */
         correction = correction_1_stor.into();
@@ -556,28 +1144,375 @@ impl ECMultContext {
     }
 }
 
+/// Window size of the table used by `ct_mul`.
+const CT_MUL_WINDOW: usize = 4;
+
+/// Multiply `point` by `scalar` using the complete addition formulas of
+/// `Projective`, so that every input is handled without special cases,
+/// including the point at infinity and a zero scalar. Constant time. This
+/// needs no context, but is slower than `ECMultContext::ecmult_const`.
+/// Requires the input's magnitudes to be at most 1. The output magnitudes
+/// are 1.
+pub fn ct_mul(point: &Projective, scalar: &Scalar) -> Projective {
+    // table[i] = i*point, including 0*point, the point at infinity.
+    let mut table = [Projective::default(); 1 << CT_MUL_WINDOW];
+    table[1] = *point;
+    for i in 2..table.len() {
+        table[i] = table[i - 1].add(point);
+    }
+
+    let mut r = Projective::default();
+    let mut t = Projective::default();
+    for window in (0..(256 / CT_MUL_WINDOW)).rev() {
+        for _ in 0..CT_MUL_WINDOW {
+            r = r.double();
+        }
+        let bits = scalar.bits(window * CT_MUL_WINDOW, CT_MUL_WINDOW);
+        for (i, entry) in table.iter().enumerate() {
+            t.cmov(entry, (i as u32).ct_eq(&bits));
+        }
+        r = r.add(&t);
+    }
+
+    for entry in table.iter_mut() {
+        entry.clear();
+    }
+    t.clear();
+    r
+}
+
 impl ECMultGenContext {
     pub fn ecmult_gen(&self, r: &mut Jacobian, gn: &Scalar) {
+        // The comb computes `sum((2*bit(d, i) - 1) * 2^i * G/2)` over all
+        // `ECMULT_GEN_COMB_BITS` bits of `d`, which equals
+        // `(d - GEN_SCALAR_DIFF)*G`. With `d = gn + blind + GEN_SCALAR_DIFF`
+        // this is `(gn + blind)*G`, and adding `ge_offset = -blind*G` gives
+        // the result.
         let mut adds = AffineStorage::default();
-        *r = self.initial;
-
-        let mut gnb = gn + &self.blind;
         let mut add = Affine::default();
-        add.infinity = false;
+        let mut neg = Field::default();
+        let mut recoded = [0u32; (ECMULT_GEN_COMB_BITS + 31) >> 5];
+
+        let mut d = gn + &self.blind;
+        d += &GEN_SCALAR_DIFF;
+        for (limb, v) in recoded.iter_mut().zip(d.0.iter()) {
+            *limb = *v;
+        }
+        d.clear();
+
+        let mut first = true;
+        let mut comb_off = ECMULT_GEN_COMB_SPACING - 1;
+        loop {
+            let mut bit_pos = comb_off;
+            for block in 0..ECMULT_GEN_COMB_BLOCKS {
+                // Gather the bits at the teeth of this block. The volatile
+                // mask keeps the compiler from turning this into a
+                // data-dependent branch.
+                let mut bits: u32 = 0;
+                for tooth in 0..ECMULT_GEN_COMB_TEETH {
+                    let bitdata = recoded[bit_pos >> 5].rotate_right((bit_pos & 0x1f) as u32);
+                    let mask = !(1u32 << tooth);
+                    bits &= unsafe { core::ptr::read_volatile(&mask) };
+                    bits ^= bitdata << tooth;
+                    bit_pos += ECMULT_GEN_COMB_SPACING;
+                }
 
-        for j in 0..64 {
-            let mut bits = gnb.bits(j * 4, 4);
-            for i in 0..16 {
-                adds.cmov(&self.prec[j][i], i as u32 == bits);
+                // The top tooth selects the sign, and the remaining ones the
+                // table entry, after flipping them for negative entries.
+                let sign = (bits >> (ECMULT_GEN_COMB_TEETH - 1)) & 1;
+                let abs = (bits ^ sign.wrapping_neg()) & (ECMULT_GEN_COMB_POINTS as u32 - 1);
+                for index in 0..ECMULT_GEN_COMB_POINTS {
+                    adds.cmov(&self.prec[block][index], (index as u32).ct_eq(&abs));
+                }
+                add = adds.into();
+                neg = add.y.neg(1);
+                add.y.cmov(&neg, sign.ct_eq(&1));
+
+                if first {
+                    r.set_ge(&add);
+                    r.rescale(&self.proj_blind);
+                    first = false;
+                } else {
+                    *r = r.add_ge(&add);
+                }
+                bits.zeroize();
             }
-            add = adds.into();
-            *r = r.add_ge(&add);
-            #[allow(unused_assignments)]
-            {
-                bits = 0;
+
+            if comb_off == 0 {
+                break;
             }
+            comb_off -= 1;
+            let r2 = *r;
+            r.double_nonzero_in_place(&r2, None);
         }
+
+        *r = r.add_ge(&self.ge_offset);
+
+        adds.clear();
         add.clear();
-        gnb.clear();
+        neg.clear();
+        recoded.zeroize();
+    }
+}
+
+/// Magic bytes at the start of a serialized context table.
+const TABLE_MAGIC: [u8; 4] = *b"S2KT";
+/// Version of the serialized context table format.
+const TABLE_VERSION: u16 = 1;
+const TABLE_KIND_ECMULT: u8 = 0;
+const TABLE_KIND_ECMULT_GEN: u8 = 1;
+/// Size of the header: magic, version, kind, a reserved byte, three
+/// parameters and the entry count.
+const TABLE_HEADER_SIZE: usize = 24;
+/// Size of one entry, the big-endian `x` and `y` coordinates.
+const TABLE_ENTRY_SIZE: usize = 64;
+/// Size of the trailing checksum.
+const TABLE_CHECKSUM_SIZE: usize = 8;
+/// Number of entries, spread over the table, checked to be on the curve
+/// when loading.
+const TABLE_SPOT_CHECKS: usize = 64;
+
+/// 64-bit FNV-1a. This only detects accidental corruption, and is not a
+/// substitute for authenticating tables loaded from untrusted storage.
+fn table_checksum(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(feature = "alloc")]
+fn table_serialize(kind: u8, params: [u32; 3], entries: &[AffineStorage]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(
+        TABLE_HEADER_SIZE + entries.len() * TABLE_ENTRY_SIZE + TABLE_CHECKSUM_SIZE,
+    );
+    ret.extend_from_slice(&TABLE_MAGIC);
+    ret.extend_from_slice(&TABLE_VERSION.to_le_bytes());
+    ret.push(kind);
+    ret.push(0);
+    for param in &params {
+        ret.extend_from_slice(&param.to_le_bytes());
+    }
+    ret.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for entry in entries {
+        let ge: Affine = (*entry).into();
+        ret.extend_from_slice(&ge.x.b32());
+        ret.extend_from_slice(&ge.y.b32());
+    }
+    let checksum = table_checksum(&ret);
+    ret.extend_from_slice(&checksum.to_le_bytes());
+    ret
+}
+
+/// Check the header and checksum of a serialized table, and return its
+/// entries.
+fn table_entries(bytes: &[u8], kind: u8, params: [u32; 3], count: usize) -> Result<&[u8], Error> {
+    let expected = TABLE_HEADER_SIZE + count * TABLE_ENTRY_SIZE + TABLE_CHECKSUM_SIZE;
+    if bytes.len() != expected {
+        return Err(Error::length(Input::Table, expected, bytes.len()));
+    }
+    let (data, checksum) = bytes.split_at(bytes.len() - TABLE_CHECKSUM_SIZE);
+    let mut checksum_le = [0u8; TABLE_CHECKSUM_SIZE];
+    checksum_le.copy_from_slice(checksum);
+    if table_checksum(data) != u64::from_le_bytes(checksum_le) {
+        return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::Checksum));
+    }
+
+    let (header, entries) = data.split_at(TABLE_HEADER_SIZE);
+    let read_u32 = |offset: usize| {
+        let mut le = [0u8; 4];
+        le.copy_from_slice(&header[offset..offset + 4]);
+        u32::from_le_bytes(le)
+    };
+    if header[0..4] != TABLE_MAGIC
+        || header[4..6] != TABLE_VERSION.to_le_bytes()
+        || header[6] != kind
+        || header[7] != 0
+        || read_u32(8) != params[0]
+        || read_u32(12) != params[1]
+        || read_u32(16) != params[2]
+        || read_u32(20) as usize != count
+    {
+        return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::Header));
+    }
+
+    Ok(entries)
+}
+
+/// Parse the serialized entries into `out`. Every coordinate must be
+/// canonically encoded, and a sample of the entries must be on the curve.
+fn table_parse_entries(entries: &[u8], out: &mut [AffineStorage]) -> Result<(), Error> {
+    let last = out.len() - 1;
+    let stride = core::cmp::max(out.len() / TABLE_SPOT_CHECKS, 1);
+    for (i, (chunk, entry)) in entries
+        .chunks(TABLE_ENTRY_SIZE)
+        .zip(out.iter_mut())
+        .enumerate()
+    {
+        let mut ge = Affine::default();
+        let mut x_b32 = [0u8; 32];
+        let mut y_b32 = [0u8; 32];
+        x_b32.copy_from_slice(&chunk[0..32]);
+        y_b32.copy_from_slice(&chunk[32..64]);
+        let mut x = Field::default();
+        let mut y = Field::default();
+        if !x.set_b32(&x_b32) || !y.set_b32(&y_b32) {
+            return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::FieldOverflow));
+        }
+        ge.set_xy(&x, &y);
+        if (i % stride == 0 || i == last) && !ge.is_valid_var() {
+            return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::NotOnCurve));
+        }
+        *entry = ge.into();
+    }
+    Ok(())
+}
+
+fn table_eq(a: &Affine, b: &Affine) -> bool {
+    let mut a = *a;
+    let mut b = *b;
+    a.x.normalize();
+    a.y.normalize();
+    b.x.normalize();
+    b.y.normalize();
+    a.x == b.x && a.y == b.y
+}
+
+impl ECMultContext {
+    /// Serialize the precomputed table in a stable, versioned binary format,
+    /// which can be loaded back with `ECMultContext::from_bytes`.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        table_serialize(TABLE_KIND_ECMULT, [WINDOW_G as u32, 0, 0], &self.pre_g)
+    }
+
+    /// Load a table serialized with `ECMultContext::to_bytes`. The table
+    /// must have been generated with the same `WINDOW_G`. The checksum is
+    /// verified, and the table is spot-checked: all coordinates must be
+    /// canonical, a sample of entries must be on the curve, and the first
+    /// two entries must be `G` and `3G`.
+    #[cfg(feature = "alloc")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Box<Self>, Error> {
+        // Allocate on the heap for the same reason as in `new_boxed`. The
+        // allocation is only treated as initialized once loading succeeded.
+        unsafe {
+            let layout = Layout::new::<ECMultContext>();
+            let ptr = alloc(layout) as *mut MaybeUninit<ECMultContext>;
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+            let mut this = Box::from_raw(ptr);
+            Self::from_bytes_in_place(bytes, &mut this)?;
+            Ok(Box::from_raw(Box::into_raw(this) as *mut ECMultContext))
+        }
+    }
+
+    /// Same as `ECMultContext::from_bytes`, loading the table into
+    /// caller-provided storage without allocating.
+    pub fn from_bytes_in_place<'a>(
+        bytes: &[u8],
+        out: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, Error> {
+        let entries = table_entries(
+            bytes,
+            TABLE_KIND_ECMULT,
+            [WINDOW_G as u32, 0, 0],
+            ECMULT_TABLE_SIZE_G,
+        )?;
+
+        // The table is fully overwritten when parsing succeeds.
+        let this = unsafe { zeroed_in_place(out) };
+        table_parse_entries(entries, &mut this.pre_g)?;
+
+        let mut gj = Jacobian::default();
+        gj.set_ge(&AFFINE_G);
+        let mut g3 = Affine::default();
+        g3.set_gej_var(&gj.double_var(None).add_ge_var(&AFFINE_G, None));
+        if !table_eq(&this.pre_g[0].into(), &AFFINE_G)
+            || (ECMULT_TABLE_SIZE_G > 1 && !table_eq(&this.pre_g[1].into(), &g3))
+        {
+            return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::Generator));
+        }
+
+        Ok(this)
+    }
+}
+
+impl ECMultGenContext {
+    /// Serialize the precomputed table in a stable, versioned binary format,
+    /// which can be loaded back with `ECMultGenContext::from_bytes`. The
+    /// blinding is not included.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        table_serialize(
+            TABLE_KIND_ECMULT_GEN,
+            [
+                ECMULT_GEN_COMB_BLOCKS as u32,
+                ECMULT_GEN_COMB_TEETH as u32,
+                ECMULT_GEN_COMB_SPACING as u32,
+            ],
+            self.prec.as_flattened(),
+        )
+    }
+
+    /// Load a table serialized with `ECMultGenContext::to_bytes`. The table
+    /// must have been generated with the same comb configuration. The
+    /// checksum is verified, and the table is spot-checked: all coordinates
+    /// must be canonical, a sample of entries must be on the curve, and the
+    /// first two entries must differ by `G`. The context starts out with
+    /// the default blinding, see `ECMultGenContext::randomize`.
+    #[cfg(feature = "alloc")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Box<Self>, Error> {
+        // Allocate on the heap for the same reason as in `new_boxed`. The
+        // allocation is only treated as initialized once loading succeeded.
+        unsafe {
+            let layout = Layout::new::<ECMultGenContext>();
+            let ptr = alloc(layout) as *mut MaybeUninit<ECMultGenContext>;
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+            let mut this = Box::from_raw(ptr);
+            Self::from_bytes_in_place(bytes, &mut this)?;
+            Ok(Box::from_raw(Box::into_raw(this) as *mut ECMultGenContext))
+        }
+    }
+
+    /// Same as `ECMultGenContext::from_bytes`, loading the table into
+    /// caller-provided storage without allocating.
+    pub fn from_bytes_in_place<'a>(
+        bytes: &[u8],
+        out: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, Error> {
+        let entries = table_entries(
+            bytes,
+            TABLE_KIND_ECMULT_GEN,
+            [
+                ECMULT_GEN_COMB_BLOCKS as u32,
+                ECMULT_GEN_COMB_TEETH as u32,
+                ECMULT_GEN_COMB_SPACING as u32,
+            ],
+            ECMULT_GEN_COMB_BLOCKS * ECMULT_GEN_COMB_POINTS,
+        )?;
+
+        // Every field is overwritten when parsing succeeds.
+        let this = unsafe { zeroed_in_place(out) };
+        table_parse_entries(entries, this.prec.as_flattened_mut())?;
+
+        let first: Affine = this.prec[0][0].into();
+        let mut diff = Jacobian::default();
+        diff.set_ge(&this.prec[0][1].into());
+        let mut diff_ge = Affine::default();
+        diff_ge.set_gej_var(&diff.add_ge_var(&first.neg(), None));
+        if diff_ge.is_infinity() || !table_eq(&diff_ge, &AFFINE_G) {
+            return Err(Error::new(ErrorKind::InvalidTable).with_detail(ErrorDetail::Generator));
+        }
+
+        this.blind = GEN_BLIND;
+        this.ge_offset = GEN_GE_OFFSET;
+        this.proj_blind = GEN_PROJ_BLIND;
+
+        Ok(this)
     }
 }
@@ -1,5 +1,8 @@
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub enum Error {
+/// The kind of an `Error`, which is stable across releases and is the one
+/// to match on.
+#[non_exhaustive]
+pub enum ErrorKind {
     InvalidSignature,
     InvalidPublicKey,
     InvalidSecretKey,
@@ -8,22 +11,443 @@ pub enum Error {
     InvalidInputLength,
     TweakOutOfRange,
     InvalidAffine,
+    InvalidTable,
+    SecureMemory,
+    FaultDetected,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// The input that could not be used.
+#[non_exhaustive]
+pub enum Input {
+    Signature,
+    PublicKey,
+    SecretKey,
+    RecoveryId,
+    Message,
+    Tweak,
+    Table,
+    Token,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why an input was rejected.
+#[non_exhaustive]
+pub enum ErrorDetail {
+    /// No more detail than the kind of the error.
+    None,
+    /// The input does not have the expected length.
+    Length { expected: usize, actual: usize },
+    /// The length of the input does not match any accepted format.
+    UnsupportedLength(usize),
+    /// The DER encoding of a signature is invalid.
+    Der(DerError),
+    /// A signature does not verify.
+    Verify(VerifyError),
+    /// An input in a key or message format of a higher-level crate, such
+    /// as a JWK or a COSE_Sign1, is invalid.
+    Encoding(EncodingError),
+    /// The leading tag byte of a public key is invalid or does not match
+    /// the requested format.
+    InvalidTag(u8),
+    /// A coordinate is not below the field prime.
+    FieldOverflow,
+    /// A scalar is not below the group order.
+    ScalarOverflow,
+    /// A scalar is zero.
+    ZeroScalar,
+    /// A point is not on the curve.
+    NotOnCurve,
+    /// A point, or the result of a computation, is the point at infinity.
+    Infinity,
+    /// The parity of the Y coordinate does not match the hybrid tag.
+    ParityMismatch,
+    /// A value is outside of its allowed range.
+    OutOfRange,
+    /// The checksum of a serialized table does not match.
+    Checksum,
+    /// The header of a serialized table does not match the configuration.
+    Header,
+    /// The entries of a serialized table are not multiples of the
+    /// generator.
+    Generator,
+    /// The PEM armor or its label is invalid, or the body is not base64.
+    Pem,
+    /// An encrypted key could not be decrypted, because the password is
+    /// wrong or the data is corrupted.
+    Decryption,
+    /// The public key given with a secret key is not the one of the secret
+    /// key.
+    KeyMismatch,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// An error, with its `kind` and, where known, the input that failed and
+/// the reason.
+///
+/// `Error` used to be an enum of what is now `ErrorKind`. Code matching on
+/// its variants matches on the kind instead:
+///
+/// ```
+/// use libsecp256k1_core::{Error, ErrorKind};
+///
+/// fn describe(err: Error) -> &'static str {
+///     match ErrorKind::from(err) {
+///         ErrorKind::InvalidSignature => "invalid signature",
+///         ErrorKind::InvalidPublicKey => "invalid public key",
+///         _ => "other error",
+///     }
+/// }
+///
+/// assert_eq!(describe(ErrorKind::InvalidSignature.into()), "invalid signature");
+/// ```
+pub struct Error {
+    kind: ErrorKind,
+    input: Option<Input>,
+    detail: ErrorDetail,
+}
+
+impl Error {
+    /// Create an error of the given kind, without detail. The input is
+    /// derived from the kind where possible.
+    pub const fn new(kind: ErrorKind) -> Self {
+        let input = match kind {
+            ErrorKind::InvalidSignature => Some(Input::Signature),
+            ErrorKind::InvalidPublicKey | ErrorKind::InvalidAffine => Some(Input::PublicKey),
+            ErrorKind::InvalidSecretKey => Some(Input::SecretKey),
+            ErrorKind::InvalidRecoveryId => Some(Input::RecoveryId),
+            ErrorKind::InvalidMessage => Some(Input::Message),
+            ErrorKind::TweakOutOfRange => Some(Input::Tweak),
+            ErrorKind::InvalidTable => Some(Input::Table),
+            ErrorKind::InvalidInputLength | ErrorKind::SecureMemory | ErrorKind::FaultDetected => {
+                None
+            }
+        };
+        Error {
+            kind,
+            input,
+            detail: ErrorDetail::None,
+        }
+    }
+
+    /// Create an `InvalidInputLength` error for the given input.
+    pub const fn length(input: Input, expected: usize, actual: usize) -> Self {
+        Error {
+            kind: ErrorKind::InvalidInputLength,
+            input: Some(input),
+            detail: ErrorDetail::Length { expected, actual },
+        }
+    }
+
+    /// Set the input that failed.
+    pub const fn with_input(mut self, input: Input) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Set the reason of the error.
+    pub const fn with_detail(mut self, detail: ErrorDetail) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub const fn input(&self) -> Option<Input> {
+        self.input
+    }
+
+    pub const fn detail(&self) -> ErrorDetail {
+        self.detail
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error::new(kind)
+    }
+}
+
+impl From<Error> for ErrorKind {
+    fn from(err: Error) -> ErrorKind {
+        err.kind
+    }
+}
+
+impl From<DerError> for Error {
+    fn from(err: DerError) -> Error {
+        Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::Der(err))
+    }
+}
+
+impl From<VerifyError> for Error {
+    fn from(err: VerifyError) -> Error {
+        Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::Verify(err))
+    }
+}
+
+impl PartialEq<ErrorKind> for Error {
+    fn eq(&self, other: &ErrorKind) -> bool {
+        self.kind == *other
+    }
 }
 
 #[cfg(feature = "std")]
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.detail {
+            ErrorDetail::Der(err) => Some(err),
+            ErrorDetail::Verify(err) => Some(err),
+            ErrorDetail::Encoding(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ErrorKind::InvalidSignature => write!(f, "Invalid signature"),
+            ErrorKind::InvalidPublicKey => write!(f, "Invalid public key"),
+            ErrorKind::InvalidSecretKey => write!(f, "Invalid secret key"),
+            ErrorKind::InvalidRecoveryId => write!(f, "Invalid recovery ID"),
+            ErrorKind::InvalidMessage => write!(f, "Invalid message"),
+            ErrorKind::InvalidInputLength => write!(f, "Invalid input length"),
+            ErrorKind::TweakOutOfRange => write!(f, "Tweak out of range"),
+            ErrorKind::InvalidAffine => write!(f, "Invalid Affine"),
+            ErrorKind::InvalidTable => write!(f, "Invalid precomputed table"),
+            ErrorKind::SecureMemory => write!(f, "Failed to allocate locked memory"),
+            ErrorKind::FaultDetected => write!(f, "Fault detected while signing"),
+        }
+    }
+}
+
+impl core::fmt::Display for Input {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Input::Signature => write!(f, "signature"),
+            Input::PublicKey => write!(f, "public key"),
+            Input::SecretKey => write!(f, "secret key"),
+            Input::RecoveryId => write!(f, "recovery ID"),
+            Input::Message => write!(f, "message"),
+            Input::Tweak => write!(f, "tweak"),
+            Input::Table => write!(f, "table"),
+            Input::Token => write!(f, "token"),
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorDetail {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ErrorDetail::None => Ok(()),
+            ErrorDetail::Length { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+            ErrorDetail::UnsupportedLength(actual) => write!(f, "unsupported length {}", actual),
+            ErrorDetail::Der(err) => write!(f, "{}", err),
+            ErrorDetail::Verify(err) => write!(f, "{}", err),
+            ErrorDetail::Encoding(err) => write!(f, "{}", err),
+            ErrorDetail::InvalidTag(tag) => write!(f, "invalid tag 0x{:02x}", tag),
+            ErrorDetail::FieldOverflow => write!(f, "coordinate not below the field prime"),
+            ErrorDetail::ScalarOverflow => write!(f, "scalar not below the group order"),
+            ErrorDetail::ZeroScalar => write!(f, "scalar is zero"),
+            ErrorDetail::NotOnCurve => write!(f, "point not on the curve"),
+            ErrorDetail::Infinity => write!(f, "point at infinity"),
+            ErrorDetail::ParityMismatch => write!(f, "Y parity does not match the tag"),
+            ErrorDetail::OutOfRange => write!(f, "value out of range"),
+            ErrorDetail::Checksum => write!(f, "checksum mismatch"),
+            ErrorDetail::Header => write!(f, "header mismatch"),
+            ErrorDetail::Generator => write!(f, "entries are not multiples of the generator"),
+            ErrorDetail::Pem => write!(f, "invalid PEM encoding"),
+            ErrorDetail::Decryption => write!(f, "decryption failed"),
+            ErrorDetail::KeyMismatch => write!(f, "public key does not match the secret key"),
+        }
+    }
+}
 
 impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match (self.kind, self.input, self.detail) {
+            (ErrorKind::InvalidInputLength, Some(input), ErrorDetail::None) => {
+                write!(f, " of {}", input)
+            }
+            (ErrorKind::InvalidInputLength, Some(input), detail) => {
+                write!(f, " of {}: {}", input, detail)
+            }
+            (_, _, ErrorDetail::None) => Ok(()),
+            (_, _, detail) => write!(f, ": {}", detail),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// The BIP-66 rule violated by a DER-encoded signature, in the order the
+/// rules are checked. The parsers that accept more than BIP-66 also use
+/// these to report malformed encodings, and the key parsers add the
+/// variants after `MissingSighash`.
+#[non_exhaustive]
+pub enum DerError {
+    /// Shorter than the 8 bytes of the smallest signature, or ends before
+    /// the encoded signature does.
+    TooShort,
+    /// Longer than the 72 bytes of the largest signature.
+    TooLong,
+    /// Does not start with the sequence tag `0x30`.
+    NotSequence,
+    /// The sequence length does not cover exactly the rest of the input.
+    InvalidSequenceLength,
+    /// The length of R runs past the end of the input.
+    InvalidRLength,
+    /// The length of S does not cover exactly the rest of the input.
+    InvalidSLength,
+    /// R does not have the integer tag `0x02`.
+    RNotInteger,
+    /// R is empty.
+    RZeroLength,
+    /// R is negative.
+    RNegative,
+    /// R has a leading zero byte that is not needed for its sign.
+    RExcessPadding,
+    /// S does not have the integer tag `0x02`.
+    SNotInteger,
+    /// S is empty.
+    SZeroLength,
+    /// S is negative.
+    SNegative,
+    /// S has a leading zero byte that is not needed for its sign.
+    SExcessPadding,
+    /// The input is empty, so there is no sighash byte to split off.
+    MissingSighash,
+    /// An element does not have the expected tag.
+    UnexpectedTag,
+    /// The length of an element is not encoded in its shortest form, or
+    /// runs past the end of the input.
+    InvalidLength,
+    /// An integer is negative, not minimally encoded, or too large.
+    InvalidInteger,
+    /// A bit string has unused bits.
+    InvalidBitString,
+    /// There are bytes after the end of the encoded structure.
+    TrailingData,
+    /// The version of the structure is not supported.
+    UnsupportedVersion,
+    /// The algorithm identifier is not `id-ecPublicKey`, or not one of the
+    /// supported encryption schemes.
+    UnsupportedAlgorithm,
+    /// The named curve is not secp256k1.
+    UnsupportedCurve,
+    /// The PBKDF2 iteration count of an encrypted key is above the maximum
+    /// accepted by the `libsecp256k1` crate.
+    TooManyIterations,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DerError {}
+
+impl core::fmt::Display for DerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DerError::TooShort => write!(f, "DER signature too short"),
+            DerError::TooLong => write!(f, "DER signature too long"),
+            DerError::NotSequence => write!(f, "DER signature is not a sequence"),
+            DerError::InvalidSequenceLength => write!(f, "Invalid DER sequence length"),
+            DerError::InvalidRLength => write!(f, "Invalid length of R"),
+            DerError::InvalidSLength => write!(f, "Invalid length of S"),
+            DerError::RNotInteger => write!(f, "R is not an integer"),
+            DerError::RZeroLength => write!(f, "R is empty"),
+            DerError::RNegative => write!(f, "R is negative"),
+            DerError::RExcessPadding => write!(f, "R has excess padding"),
+            DerError::SNotInteger => write!(f, "S is not an integer"),
+            DerError::SZeroLength => write!(f, "S is empty"),
+            DerError::SNegative => write!(f, "S is negative"),
+            DerError::SExcessPadding => write!(f, "S has excess padding"),
+            DerError::MissingSighash => write!(f, "Missing sighash byte"),
+            DerError::UnexpectedTag => write!(f, "Unexpected DER tag"),
+            DerError::InvalidLength => write!(f, "Invalid DER length"),
+            DerError::InvalidInteger => write!(f, "Invalid DER integer"),
+            DerError::InvalidBitString => write!(f, "Invalid DER bit string"),
+            DerError::TrailingData => write!(f, "Trailing data after DER structure"),
+            DerError::UnsupportedVersion => write!(f, "Unsupported version"),
+            DerError::UnsupportedAlgorithm => write!(f, "Unsupported algorithm"),
+            DerError::UnsupportedCurve => write!(f, "Unsupported curve"),
+            DerError::TooManyIterations => write!(f, "Too many PBKDF2 iterations"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why a signature does not verify, in the order the checks are made.
+#[non_exhaustive]
+pub enum VerifyError {
+    /// R is zero.
+    ZeroR,
+    /// S is zero.
+    ZeroS,
+    /// S is in the upper half of the group order, and low S is required.
+    HighS,
+    /// The public key is the point at infinity.
+    InfinityPublicKey,
+    /// The signature is well-formed, but not a signature of the message by
+    /// the public key.
+    Mismatch,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+impl core::fmt::Display for VerifyError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Error::InvalidSignature => write!(f, "Invalid signature"),
-            Error::InvalidPublicKey => write!(f, "Invalid public key"),
-            Error::InvalidSecretKey => write!(f, "Invalid secret key"),
-            Error::InvalidRecoveryId => write!(f, "Invalid recovery ID"),
-            Error::InvalidMessage => write!(f, "Invalid message"),
-            Error::InvalidInputLength => write!(f, "Invalid input length"),
-            Error::TweakOutOfRange => write!(f, "Tweak out of range"),
-            Error::InvalidAffine => write!(f, "Invalid Affine"),
+            VerifyError::ZeroR => write!(f, "R is zero"),
+            VerifyError::ZeroS => write!(f, "S is zero"),
+            VerifyError::HighS => write!(f, "S is not low"),
+            VerifyError::InfinityPublicKey => write!(f, "Public key is the point at infinity"),
+            VerifyError::Mismatch => write!(f, "Signature does not match"),
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why an input in a format defined outside of this crate is rejected.
+/// The format and code are chosen by the crate defining the format, which
+/// converts them back to its own error type.
+pub struct EncodingError {
+    format: &'static str,
+    code: u8,
+    message: &'static str,
+}
+
+impl EncodingError {
+    /// Create an error of `format` with a `code` unique within that format
+    /// and the `message` shown by `Display`.
+    pub const fn new(format: &'static str, code: u8, message: &'static str) -> Self {
+        EncodingError {
+            format,
+            code,
+            message,
+        }
+    }
+
+    /// Name of the format.
+    pub const fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// Code of the error within the format.
+    pub const fn code(&self) -> u8 {
+        self.code
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodingError {}
+
+impl core::fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -1,7 +1,12 @@
 use core::{
     cmp::Ordering,
-    ops::{Add, AddAssign, Mul, MulAssign},
+    ops::{Add, AddAssign, Mul, MulAssign, Neg},
 };
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+#[cfg(all(feature = "x86-bmi2", target_arch = "x86_64"))]
+mod x86_64;
 
 macro_rules! debug_assert_bits {
     ($x: expr, $n: expr) => {
@@ -23,6 +28,13 @@ pub struct Field {
 }
 
 impl Field {
+    /// Zero, with the same representation as `Field::default`.
+    pub(crate) const ZERO: Field = Field {
+        n: [0u32; 10],
+        magnitude: 0,
+        normalized: true,
+    };
+
     pub const fn new_raw(
         d9: u32,
         d8: u32,
@@ -70,13 +82,13 @@ impl Field {
         }
     }
 
-    pub fn from_int(a: u32) -> Field {
-        let mut f = Field::default();
+    pub const fn from_int(a: u32) -> Field {
+        let mut f = Field::ZERO;
         f.set_int(a);
         f
     }
 
-    fn verify(&self) -> bool {
+    const fn verify(&self) -> bool {
         let m = if self.normalized { 1 } else { 2 } * self.magnitude;
         let mut r = true;
         r = r && (self.n[0] <= 0x3ffffff * m);
@@ -109,7 +121,7 @@ impl Field {
     }
 
     /// Normalize a field element.
-    pub fn normalize(&mut self) {
+    pub const fn normalize(&mut self) {
         let mut t0 = self.n[0];
         let mut t1 = self.n[1];
         let mut t2 = self.n[2];
@@ -197,7 +209,7 @@ impl Field {
 
     /// Weakly normalize a field element: reduce it magnitude to 1,
     /// but don't fully normalize.
-    pub fn normalize_weak(&mut self) {
+    pub const fn normalize_weak(&mut self) {
         let mut t0 = self.n[0];
         let mut t1 = self.n[1];
         let mut t2 = self.n[2];
@@ -241,7 +253,7 @@ impl Field {
     }
 
     /// Normalize a field element, without constant-time guarantee.
-    pub fn normalize_var(&mut self) {
+    pub const fn normalize_var(&mut self) {
         let mut t0 = self.n[0];
         let mut t1 = self.n[1];
         let mut t2 = self.n[2];
@@ -333,7 +345,7 @@ impl Field {
     /// normalize to a zero value. The field implementation may
     /// optionally normalize the input, but this should not be relied
     /// upon.
-    pub fn normalizes_to_zero(&self) -> bool {
+    pub const fn normalizes_to_zero(&self) -> bool {
         let mut t0 = self.n[0];
         let mut t1 = self.n[1];
         let mut t2 = self.n[2];
@@ -401,7 +413,7 @@ impl Field {
     /// normalize to a zero value. The field implementation may
     /// optionally normalize the input, but this should not be relied
     /// upon.
-    pub fn normalizes_to_zero_var(&self) -> bool {
+    pub const fn normalizes_to_zero_var(&self) -> bool {
         let mut t0: u32;
         let mut t1: u32;
         let mut t2: u32;
@@ -484,7 +496,7 @@ impl Field {
 
     /// Set a field element equal to a small integer. Resulting field
     /// element is normalized.
-    pub fn set_int(&mut self, a: u32) {
+    pub const fn set_int(&mut self, a: u32) {
         self.n = [a, 0, 0, 0, 0, 0, 0, 0, 0, 0];
         self.magnitude = 1;
         self.normalized = true;
@@ -493,7 +505,7 @@ impl Field {
 
     /// Verify whether a field element is zero. Requires the input to
     /// be normalized.
-    pub fn is_zero(&self) -> bool {
+    pub const fn is_zero(&self) -> bool {
         debug_assert!(self.normalized);
         debug_assert!(self.verify());
         (self.n[0]
@@ -511,17 +523,16 @@ impl Field {
 
     /// Check the "oddness" of a field element. Requires the input to
     /// be normalized.
-    pub fn is_odd(&self) -> bool {
+    pub const fn is_odd(&self) -> bool {
         debug_assert!(self.normalized);
         debug_assert!(self.verify());
         self.n[0] & 1 != 0
     }
 
-    /// Sets a field element equal to zero, initializing all fields.
+    /// Sets a field element equal to zero, initializing all fields. The
+    /// same as `zeroize`.
     pub fn clear(&mut self) {
-        self.magnitude = 0;
-        self.normalized = true;
-        self.n = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        self.zeroize();
     }
 
     /// Set a field element equal to 32-byte big endian value. If
@@ -630,7 +641,7 @@ impl Field {
     /// Set a field element equal to the additive inverse of
     /// another. Takes a maximum magnitude of the input as an
     /// argument. The magnitude of the output is one higher.
-    pub fn neg_in_place(&mut self, other: &Field, m: u32) {
+    pub const fn neg_in_place(&mut self, other: &Field, m: u32) {
         debug_assert!(other.magnitude <= m);
         debug_assert!(other.verify());
 
@@ -652,15 +663,15 @@ impl Field {
 
     /// Compute the additive inverse of this element. Takes the maximum
     /// expected magnitude of this element as an argument.
-    pub fn neg(&self, m: u32) -> Field {
-        let mut ret = Field::default();
+    pub const fn neg(&self, m: u32) -> Field {
+        let mut ret = Field::ZERO;
         ret.neg_in_place(self, m);
         ret
     }
 
     /// Multiplies the passed field element with a small integer
     /// constant. Multiplies the magnitude by that small integer.
-    pub fn mul_int(&mut self, a: u32) {
+    pub const fn mul_int(&mut self, a: u32) {
         self.n[0] *= a;
         self.n[1] *= a;
         self.n[2] *= a;
@@ -703,7 +714,7 @@ impl Field {
         na.normalizes_to_zero_var()
     }
 
-    fn mul_inner(&mut self, a: &Field, b: &Field) {
+    const fn mul_inner(&mut self, a: &Field, b: &Field) {
         const M: u64 = 0x3ffffff;
         const R0: u64 = 0x3d10;
         const R1: u64 = 0x400;
@@ -1094,7 +1105,7 @@ impl Field {
         /* [r9 r8 r7 r6 r5 r4 r3 r2 r1 r0] = [p18 p17 p16 p15 p14 p13 p12 p11 p10 p9 p8 p7 p6 p5 p4 p3 p2 p1 p0] */
     }
 
-    fn sqr_inner(&mut self, a: &Field) {
+    const fn sqr_inner(&mut self, a: &Field) {
         const M: u64 = 0x3ffffff;
         const R0: u64 = 0x3d10;
         const R1: u64 = 0x400;
@@ -1429,6 +1440,45 @@ impl Field {
     /// the inputs' magnitudes to be at most 8. The output magnitude
     /// is 1 (but not guaranteed to be normalized).
     pub fn mul_in_place(&mut self, a: &Field, b: &Field) {
+        #[cfg(all(feature = "x86-bmi2", target_arch = "x86_64"))]
+        {
+            if x86_64::is_supported() {
+                debug_assert!(a.magnitude <= 8);
+                debug_assert!(b.magnitude <= 8);
+                debug_assert!(a.verify());
+                debug_assert!(b.verify());
+                // Safety: the required CPU features are available.
+                unsafe { x86_64::mul(&mut self.n, &a.n, &b.n) };
+                self.magnitude = 1;
+                self.normalized = false;
+                debug_assert!(self.verify());
+                return;
+            }
+        }
+        self.mul_portable(a, b);
+    }
+
+    /// Sets a field element to be the square of another. Requires the
+    /// input's magnitude to be at most 8. The output magnitude is 1
+    /// (but not guaranteed to be normalized).
+    pub fn sqr_in_place(&mut self, a: &Field) {
+        #[cfg(all(feature = "x86-bmi2", target_arch = "x86_64"))]
+        {
+            if x86_64::is_supported() {
+                debug_assert!(a.magnitude <= 8);
+                debug_assert!(a.verify());
+                // Safety: the required CPU features are available.
+                unsafe { x86_64::sqr(&mut self.n, &a.n) };
+                self.magnitude = 1;
+                self.normalized = false;
+                debug_assert!(self.verify());
+                return;
+            }
+        }
+        self.sqr_portable(a);
+    }
+
+    const fn mul_portable(&mut self, a: &Field, b: &Field) {
         debug_assert!(a.magnitude <= 8);
         debug_assert!(b.magnitude <= 8);
         debug_assert!(a.verify());
@@ -1439,10 +1489,7 @@ impl Field {
         debug_assert!(self.verify());
     }
 
-    /// Sets a field element to be the square of another. Requires the
-    /// input's magnitude to be at most 8. The output magnitude is 1
-    /// (but not guaranteed to be normalized).
-    pub fn sqr_in_place(&mut self, a: &Field) {
+    const fn sqr_portable(&mut self, a: &Field) {
         debug_assert!(a.magnitude <= 8);
         debug_assert!(a.verify());
         self.sqr_inner(a);
@@ -1451,12 +1498,72 @@ impl Field {
         debug_assert!(a.verify());
     }
 
+    /// Same as `*`, always using the portable implementation. Usable in
+    /// const context.
+    pub const fn mul_const(&self, other: &Field) -> Field {
+        let mut ret = Field::ZERO;
+        ret.mul_portable(self, other);
+        ret
+    }
+
+    /// Same as `sqr`, always using the portable implementation. Usable in
+    /// const context.
+    pub const fn sqr_const(&self) -> Field {
+        let mut ret = Field::ZERO;
+        ret.sqr_portable(self);
+        ret
+    }
+
     pub fn sqr(&self) -> Field {
-        let mut ret = Field::default();
+        let mut ret = Field::ZERO;
         ret.sqr_in_place(self);
         ret
     }
 
+    /// Square a field element `n` times.
+    fn sqr_n(&self, n: usize) -> Field {
+        let mut ret = *self;
+        for _ in 0..n {
+            ret = ret.sqr();
+        }
+        ret
+    }
+
+    /// Same as `+=`: add another field element, with the magnitudes adding
+    /// up. Usable in const context.
+    pub const fn add_assign_const(&mut self, other: &Field) {
+        self.n[0] += other.n[0];
+        self.n[1] += other.n[1];
+        self.n[2] += other.n[2];
+        self.n[3] += other.n[3];
+        self.n[4] += other.n[4];
+        self.n[5] += other.n[5];
+        self.n[6] += other.n[6];
+        self.n[7] += other.n[7];
+        self.n[8] += other.n[8];
+        self.n[9] += other.n[9];
+
+        self.magnitude += other.magnitude;
+        self.normalized = false;
+        debug_assert!(self.verify());
+    }
+
+    /// Convert a normalized field element to its compact storage form.
+    pub const fn to_storage(&self) -> FieldStorage {
+        debug_assert!(self.normalized);
+
+        FieldStorage([
+            self.n[0] | self.n[1] << 26,
+            self.n[1] >> 6 | self.n[2] << 20,
+            self.n[2] >> 12 | self.n[3] << 14,
+            self.n[3] >> 18 | self.n[4] << 8,
+            self.n[4] >> 24 | self.n[5] << 2 | self.n[6] << 28,
+            self.n[6] >> 4 | self.n[7] << 22,
+            self.n[7] >> 10 | self.n[8] << 16,
+            self.n[8] >> 16 | self.n[9] << 10,
+        ])
+    }
+
     /// If a has a square root, it is computed in r and 1 is
     /// returned. If a does not have a square root, the root of its
     /// negation is computed and 0 is returned. The input's magnitude
@@ -1545,82 +1652,47 @@ impl Field {
     /// output magnitude is 1 (but not guaranteed to be normalized).
     pub fn inv(&self) -> Field {
         let mut x2 = self.sqr();
-        x2 *= self;
+        x2.mul_assign(self);
 
         let mut x3 = x2.sqr();
-        x3 *= self;
+        x3.mul_assign(self);
 
-        let mut x6 = x3;
-        for _ in 0..3 {
-            x6 = x6.sqr();
-        }
-        x6 *= &x3;
+        let mut x6 = x3.sqr_n(3);
+        x6.mul_assign(&x3);
 
-        let mut x9 = x6;
-        for _ in 0..3 {
-            x9 = x9.sqr();
-        }
-        x9 *= &x3;
+        let mut x9 = x6.sqr_n(3);
+        x9.mul_assign(&x3);
 
-        let mut x11 = x9;
-        for _ in 0..2 {
-            x11 = x11.sqr();
-        }
-        x11 *= &x2;
+        let mut x11 = x9.sqr_n(2);
+        x11.mul_assign(&x2);
 
-        let mut x22 = x11;
-        for _ in 0..11 {
-            x22 = x22.sqr();
-        }
-        x22 *= &x11;
+        let mut x22 = x11.sqr_n(11);
+        x22.mul_assign(&x11);
 
-        let mut x44 = x22;
-        for _ in 0..22 {
-            x44 = x44.sqr();
-        }
-        x44 *= &x22;
+        let mut x44 = x22.sqr_n(22);
+        x44.mul_assign(&x22);
 
-        let mut x88 = x44;
-        for _ in 0..44 {
-            x88 = x88.sqr();
-        }
-        x88 *= &x44;
+        let mut x88 = x44.sqr_n(44);
+        x88.mul_assign(&x44);
 
-        let mut x176 = x88;
-        for _ in 0..88 {
-            x176 = x176.sqr();
-        }
-        x176 *= &x88;
+        let mut x176 = x88.sqr_n(88);
+        x176.mul_assign(&x88);
 
-        let mut x220 = x176;
-        for _ in 0..44 {
-            x220 = x220.sqr();
-        }
-        x220 *= &x44;
+        let mut x220 = x176.sqr_n(44);
+        x220.mul_assign(&x44);
 
-        let mut x223 = x220;
-        for _ in 0..3 {
-            x223 = x223.sqr();
-        }
-        x223 *= &x3;
+        let mut x223 = x220.sqr_n(3);
+        x223.mul_assign(&x3);
 
-        let mut t1 = x223;
-        for _ in 0..23 {
-            t1 = t1.sqr();
-        }
-        t1 *= &x22;
-        for _ in 0..5 {
-            t1 = t1.sqr();
-        }
-        t1 *= self;
-        for _ in 0..3 {
-            t1 = t1.sqr();
-        }
-        t1 *= &x2;
-        for _ in 0..2 {
-            t1 = t1.sqr();
-        }
-        self * &t1
+        let mut t1 = x223.sqr_n(23);
+        t1.mul_assign(&x22);
+        t1 = t1.sqr_n(5);
+        t1.mul_assign(self);
+        t1 = t1.sqr_n(3);
+        t1.mul_assign(&x2);
+        t1 = t1.sqr_n(2);
+        t1.mul_assign(self);
+        t1
     }
 
     /// Potentially faster version of secp256k1_fe_inv, without
@@ -1629,45 +1701,156 @@ impl Field {
         self.inv()
     }
 
-    /// Checks whether a field element is a quadratic residue.
-    pub fn is_quad_var(&self) -> bool {
-        let (_, ret) = self.sqrt();
+    /// Same as `inv`, always using the portable implementation. Usable in
+    /// const context. This uses plain square-and-multiply, and is slower
+    /// than `inv`.
+    pub const fn inv_const(&self) -> Field {
+        // p - 2, most significant word first.
+        const EXP: [u32; 8] = [
+            0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFE,
+            0xFFFFFC2D,
+        ];
+
+        let mut ret = Field::from_int(1);
+        let mut i = 0;
+        while i < 256 {
+            ret = ret.sqr_const();
+            if (EXP[i / 32] >> (31 - i % 32)) & 1 == 1 {
+                ret = ret.mul_const(self);
+            }
+            i += 1;
+        }
         ret
     }
 
+    /// Checks whether a field element is a quadratic residue. Same as
+    /// `is_square`.
+    pub fn is_quad_var(&self) -> bool {
+        self.is_square()
+    }
+
+    /// Checks whether a field element is a square, that is, whether
+    /// `sqrt` succeeds. Zero is a square. The input's magnitude can be at
+    /// most 31. Not constant time.
+    pub fn is_square(&self) -> bool {
+        self.jacobi_var() >= 0
+    }
+
+    /// Compute the Jacobi symbol of a field element modulo the field
+    /// prime: 0 for zero, 1 for non-zero squares, and -1 for non-squares.
+    /// Uses the binary algorithm, which is much faster than a square root.
+    /// The input's magnitude can be at most 31. Not constant time.
+    pub fn jacobi_var(&self) -> i32 {
+        let mut a = *self;
+        a.normalize_var();
+        let b32 = a.b32();
+        let mut a = [0u64; 4];
+        for i in 0..4 {
+            let mut limb = [0u8; 8];
+            limb.copy_from_slice(&b32[(24 - 8 * i)..(32 - 8 * i)]);
+            a[i] = u64::from_be_bytes(limb);
+        }
+        let mut n = FIELD_P_U64;
+        let mut ret = 1;
+
+        // Invariant: the result is `ret * (a/n)`, with `n` odd.
+        loop {
+            if a == [0; 4] {
+                return if n == [1, 0, 0, 0] { ret } else { 0 };
+            }
+
+            // (2/n) is -1 exactly when n is 3 or 5 mod 8.
+            let shift = u256_trailing_zeros(&a);
+            u256_shr(&mut a, shift);
+            if shift & 1 == 1 && (n[0] & 7 == 3 || n[0] & 7 == 5) {
+                ret = -ret;
+            }
+
+            // Quadratic reciprocity, for odd a and n.
+            if u256_lt(&a, &n) {
+                core::mem::swap(&mut a, &mut n);
+                if a[0] & 3 == 3 && n[0] & 3 == 3 {
+                    ret = -ret;
+                }
+            }
+
+            // (a/n) = ((a - n)/n), and a - n is even.
+            u256_sub_assign(&mut a, &n);
+        }
+    }
+
     /// If flag is true, set *r equal to *a; otherwise leave
     /// it. Constant-time.
-    pub fn cmov(&mut self, other: &Field, flag: bool) {
-        self.n[0] = if flag { other.n[0] } else { self.n[0] };
-        self.n[1] = if flag { other.n[1] } else { self.n[1] };
-        self.n[2] = if flag { other.n[2] } else { self.n[2] };
-        self.n[3] = if flag { other.n[3] } else { self.n[3] };
-        self.n[4] = if flag { other.n[4] } else { self.n[4] };
-        self.n[5] = if flag { other.n[5] } else { self.n[5] };
-        self.n[6] = if flag { other.n[6] } else { self.n[6] };
-        self.n[7] = if flag { other.n[7] } else { self.n[7] };
-        self.n[8] = if flag { other.n[8] } else { self.n[8] };
-        self.n[9] = if flag { other.n[9] } else { self.n[9] };
-        self.magnitude = if flag {
-            other.magnitude
+    pub fn cmov(&mut self, other: &Field, flag: Choice) {
+        self.conditional_assign(other, flag);
+    }
+}
+
+/// The field prime, as little-endian 64-bit limbs.
+const FIELD_P_U64: [u64; 4] = [
+    0xFFFFFFFEFFFFFC2F,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+];
+
+/// Number of trailing zero bits of a non-zero 256-bit integer.
+fn u256_trailing_zeros(a: &[u64; 4]) -> u32 {
+    let mut i = 0;
+    while a[i] == 0 {
+        i += 1;
+    }
+    i as u32 * 64 + a[i].trailing_zeros()
+}
+
+fn u256_shr(a: &mut [u64; 4], shift: u32) {
+    let limbs = (shift / 64) as usize;
+    let bits = shift % 64;
+    for i in 0..4 {
+        let lo = if i + limbs < 4 { a[i + limbs] } else { 0 };
+        let hi = if i + limbs + 1 < 4 {
+            a[i + limbs + 1]
         } else {
-            self.magnitude
+            0
         };
-        self.normalized = if flag {
-            other.normalized
+        a[i] = if bits == 0 {
+            lo
         } else {
-            self.normalized
+            lo >> bits | hi << (64 - bits)
         };
     }
 }
 
+fn u256_lt(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+fn u256_sub_assign(a: &mut [u64; 4], b: &[u64; 4]) {
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        a[i] = d;
+        borrow = b1 || b2;
+    }
+}
+
+impl Zeroize for Field {
+    fn zeroize(&mut self) {
+        self.n.zeroize();
+        self.magnitude = 0;
+        self.normalized = true;
+    }
+}
+
 impl Default for Field {
     fn default() -> Field {
-        Self {
-            n: [0u32; 10],
-            magnitude: 0,
-            normalized: true,
-        }
+        Field::ZERO
     }
 }
 
@@ -1675,7 +1858,7 @@ impl Add<Field> for Field {
     type Output = Field;
     fn add(self, other: Field) -> Field {
         let mut ret = self;
-        ret.add_assign(&other);
+        ret.add_assign_const(&other);
         ret
     }
 }
@@ -1684,27 +1867,14 @@ impl<'a, 'b> Add<&'a Field> for &'b Field {
     type Output = Field;
     fn add(self, other: &'a Field) -> Field {
         let mut ret = *self;
-        ret.add_assign(other);
+        ret.add_assign_const(other);
         ret
     }
 }
 
 impl<'a> AddAssign<&'a Field> for Field {
     fn add_assign(&mut self, other: &'a Field) {
-        self.n[0] += other.n[0];
-        self.n[1] += other.n[1];
-        self.n[2] += other.n[2];
-        self.n[3] += other.n[3];
-        self.n[4] += other.n[4];
-        self.n[5] += other.n[5];
-        self.n[6] += other.n[6];
-        self.n[7] += other.n[7];
-        self.n[8] += other.n[8];
-        self.n[9] += other.n[9];
-
-        self.magnitude += other.magnitude;
-        self.normalized = false;
-        debug_assert!(self.verify());
+        self.add_assign_const(other)
     }
 }
 
@@ -1756,6 +1926,41 @@ impl PartialEq for Field {
 
 impl Eq for Field {}
 
+impl ConstantTimeEq for Field {
+    fn ct_eq(&self, other: &Field) -> Choice {
+        let mut a = *self;
+        let mut b = *other;
+        a.normalize();
+        b.normalize();
+        a.n.ct_eq(&b.n)
+    }
+}
+
+impl ConditionallySelectable for Field {
+    fn conditional_select(a: &Field, b: &Field, choice: Choice) -> Field {
+        let mut n = [0u32; 10];
+        for i in 0..10 {
+            n[i] = u32::conditional_select(&a.n[i], &b.n[i], choice);
+        }
+        Field {
+            n,
+            magnitude: u32::conditional_select(&a.magnitude, &b.magnitude, choice),
+            normalized: u8::conditional_select(
+                &(a.normalized as u8),
+                &(b.normalized as u8),
+                choice,
+            ) != 0,
+        }
+    }
+}
+
+impl Neg for &Field {
+    type Output = Field;
+    fn neg(self) -> Field {
+        Field::neg(self, self.magnitude)
+    }
+}
+
 impl Ord for Field {
     fn cmp(&self, other: &Field) -> Ordering {
         self.cmp_var(other)
@@ -1792,15 +1997,32 @@ impl FieldStorage {
         Self([d0, d1, d2, d3, d4, d5, d6, d7])
     }
 
-    pub fn cmov(&mut self, other: &FieldStorage, flag: bool) {
-        self.0[0] = if flag { other.0[0] } else { self.0[0] };
-        self.0[1] = if flag { other.0[1] } else { self.0[1] };
-        self.0[2] = if flag { other.0[2] } else { self.0[2] };
-        self.0[3] = if flag { other.0[3] } else { self.0[3] };
-        self.0[4] = if flag { other.0[4] } else { self.0[4] };
-        self.0[5] = if flag { other.0[5] } else { self.0[5] };
-        self.0[6] = if flag { other.0[6] } else { self.0[6] };
-        self.0[7] = if flag { other.0[7] } else { self.0[7] };
+    /// If flag is true, set *r equal to *a; otherwise leave
+    /// it. Constant-time.
+    pub fn cmov(&mut self, other: &FieldStorage, flag: Choice) {
+        self.conditional_assign(other, flag);
+    }
+}
+
+impl ConstantTimeEq for FieldStorage {
+    fn ct_eq(&self, other: &FieldStorage) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for FieldStorage {
+    fn conditional_select(a: &FieldStorage, b: &FieldStorage, choice: Choice) -> FieldStorage {
+        let mut r = [0u32; 8];
+        for i in 0..8 {
+            r[i] = u32::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        FieldStorage(r)
+    }
+}
+
+impl Zeroize for FieldStorage {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
     }
 }
 
@@ -1828,18 +2050,6 @@ impl From<FieldStorage> for Field {
 
 impl Into<FieldStorage> for Field {
     fn into(self) -> FieldStorage {
-        debug_assert!(self.normalized);
-        let mut r = FieldStorage::default();
-
-        r.0[0] = self.n[0] | self.n[1] << 26;
-        r.0[1] = self.n[1] >> 6 | self.n[2] << 20;
-        r.0[2] = self.n[2] >> 12 | self.n[3] << 14;
-        r.0[3] = self.n[3] >> 18 | self.n[4] << 8;
-        r.0[4] = self.n[4] >> 24 | self.n[5] << 2 | self.n[6] << 28;
-        r.0[5] = self.n[6] >> 4 | self.n[7] << 22;
-        r.0[6] = self.n[7] >> 10 | self.n[8] << 16;
-        r.0[7] = self.n[8] >> 16 | self.n[9] << 10;
-
-        r
+        self.to_storage()
     }
 }
@@ -0,0 +1,188 @@
+//! Field multiplication and squaring for x86_64 CPUs with the BMI2 and ADX
+//! extensions, using the `mulx`, `adcx` and `adox` instructions.
+//!
+//! Elements are converted from the 10x26 representation to four 64-bit
+//! limbs, multiplied, reduced modulo `p`, and converted back. All inputs of
+//! magnitude at most 8 are accepted, and the output is always below
+//! `2^256`, which is a valid magnitude 1 element. Constant time.
+
+use core::arch::x86_64::{_addcarryx_u64, _mulx_u64};
+
+/// `2^256 - p`.
+const R: u64 = 0x1000003D1;
+
+/// Check whether the CPU supports the instructions used by this backend.
+/// Without `std`, this is only the case if they are enabled at compile
+/// time. Otherwise the CPU is queried once, on the first call.
+#[inline]
+pub fn is_supported() -> bool {
+    #[cfg(all(target_feature = "bmi2", target_feature = "adx"))]
+    {
+        true
+    }
+    #[cfg(all(
+        feature = "std",
+        not(all(target_feature = "bmi2", target_feature = "adx"))
+    ))]
+    {
+        detection::is_supported()
+    }
+    #[cfg(all(
+        not(feature = "std"),
+        not(all(target_feature = "bmi2", target_feature = "adx"))
+    ))]
+    {
+        false
+    }
+}
+
+/// Runtime detection, cached so that the field multiplication only pays
+/// for an atomic load.
+#[cfg(all(
+    feature = "std",
+    not(all(target_feature = "bmi2", target_feature = "adx"))
+))]
+mod detection {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const UNKNOWN: u8 = 0;
+    const SUPPORTED: u8 = 1;
+    const UNSUPPORTED: u8 = 2;
+
+    static DETECTED: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    #[inline]
+    pub fn is_supported() -> bool {
+        match DETECTED.load(Ordering::Relaxed) {
+            SUPPORTED => true,
+            UNSUPPORTED => false,
+            _ => detect(),
+        }
+    }
+
+    #[cold]
+    fn detect() -> bool {
+        let supported =
+            std::is_x86_feature_detected!("bmi2") && std::is_x86_feature_detected!("adx");
+        DETECTED.store(
+            if supported { SUPPORTED } else { UNSUPPORTED },
+            Ordering::Relaxed,
+        );
+        supported
+    }
+}
+
+/// Set `r = a * b mod p`.
+///
+/// # Safety
+/// The CPU must support BMI2 and ADX, see `is_supported`.
+#[target_feature(enable = "bmi2,adx")]
+pub unsafe fn mul(r: &mut [u32; 10], a: &[u32; 10], b: &[u32; 10]) {
+    let a = from_10x26(a);
+    let b = from_10x26(b);
+    to_10x26(&reduce_wide(&mul_wide(&a, &b)), r);
+}
+
+/// Set `r = a^2 mod p`.
+///
+/// # Safety
+/// The CPU must support BMI2 and ADX, see `is_supported`.
+#[target_feature(enable = "bmi2,adx")]
+pub unsafe fn sqr(r: &mut [u32; 10], a: &[u32; 10]) {
+    let a = from_10x26(a);
+    to_10x26(&reduce_wide(&mul_wide(&a, &a)), r);
+}
+
+/// Full 512-bit product of two 256-bit integers.
+#[inline(always)]
+unsafe fn mul_wide(a: &[u64; 4], b: &[u64; 4]) -> [u64; 8] {
+    let mut r = [0u64; 8];
+    for i in 0..4 {
+        let mut lo = [0u64; 4];
+        let mut hi = [0u64; 4];
+        for j in 0..4 {
+            lo[j] = _mulx_u64(a[j], b[i], &mut hi[j]);
+        }
+
+        // Rows before this one only wrote `r[..i + 4]`. The running sum
+        // is below `2^(64 * (i + 5))`, so the second chain cannot carry
+        // out.
+        let mut c = 0;
+        for j in 0..4 {
+            c = _addcarryx_u64(c, r[i + j], lo[j], &mut r[i + j]);
+        }
+        r[i + 4] = c as u64;
+        let mut c = 0;
+        for j in 0..4 {
+            c = _addcarryx_u64(c, r[i + j + 1], hi[j], &mut r[i + j + 1]);
+        }
+        debug_assert!(c == 0);
+    }
+    r
+}
+
+/// Add `c * R` to `t`, returning the carry out of the top limb.
+#[inline(always)]
+fn add_mul_r(t: &mut [u64; 4], c: u64) -> u64 {
+    let mut v = c as u128 * R as u128;
+    for limb in t.iter_mut() {
+        v += *limb as u128;
+        *limb = v as u64;
+        v >>= 64;
+    }
+    v as u64
+}
+
+/// Reduce a 512-bit integer below `2^256`, using `2^256 = R mod p`.
+#[inline(always)]
+fn reduce_wide(r: &[u64; 8]) -> [u64; 4] {
+    let mut t = [0u64; 4];
+    let mut v = 0u128;
+    for j in 0..4 {
+        v += r[j] as u128 + r[j + 4] as u128 * R as u128;
+        t[j] = v as u64;
+        v >>= 64;
+    }
+    // A carry out of the first addition leaves `t` small, so the second
+    // one cannot carry out.
+    let c = add_mul_r(&mut t, v as u64);
+    add_mul_r(&mut t, c);
+    t
+}
+
+/// Convert from the 10x26 representation, reducing below `2^256`.
+#[inline(always)]
+fn from_10x26(n: &[u32; 10]) -> [u64; 4] {
+    let mut t = [0u64; 4];
+    let mut acc = 0u128;
+    let mut acc_bits = 0;
+    let mut j = 0;
+    for limb in n.iter() {
+        acc += (*limb as u128) << acc_bits;
+        acc_bits += 26;
+        if acc_bits >= 64 {
+            t[j] = acc as u64;
+            acc >>= 64;
+            acc_bits -= 64;
+            j += 1;
+        }
+    }
+    // `acc` holds the bits above 2^256.
+    let c = add_mul_r(&mut t, acc as u64);
+    add_mul_r(&mut t, c);
+    t
+}
+
+/// Convert to the 10x26 representation. The input must be below `2^256`.
+#[inline(always)]
+fn to_10x26(t: &[u64; 4], n: &mut [u32; 10]) {
+    for (i, limb) in n.iter_mut().enumerate() {
+        let bit = 26 * i;
+        let (w, off) = (bit / 64, bit % 64);
+        let mut v = t[w] >> off;
+        if off > 38 && w + 1 < 4 {
+            v |= t[w + 1] << (64 - off);
+        }
+        *limb = (v & if i == 9 { 0x3fffff } else { 0x3ffffff }) as u32;
+    }
+}
@@ -1,4 +1,7 @@
 use crate::field::{Field, FieldStorage};
+use core::ops::Neg;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 /// A group element of the secp256k1 curve, in affine coordinates.
@@ -17,6 +20,17 @@ pub struct Jacobian {
     pub infinity: bool,
 }
 
+#[derive(Debug, Clone, Copy)]
+/// A group element of the secp256k1 curve, in homogeneous projective
+/// coordinates `(x:y:z)`, representing the affine point `(x/z, y/z)`. The
+/// point at infinity is any `(0:y:0)`, without a separate flag, which allows
+/// complete addition formulas without special cases.
+pub struct Projective {
+    pub x: Field,
+    pub y: Field,
+    pub z: Field,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 /// Affine coordinate group element compact storage.
 pub struct AffineStorage {
@@ -36,21 +50,19 @@ impl Default for Affine {
 
 impl Default for Jacobian {
     fn default() -> Jacobian {
-        Jacobian {
-            x: Field::default(),
-            y: Field::default(),
-            z: Field::default(),
-            infinity: false,
-        }
+        Jacobian::ZERO
+    }
+}
+
+impl Default for Projective {
+    fn default() -> Projective {
+        PROJECTIVE_INFINITY
     }
 }
 
 impl Default for AffineStorage {
     fn default() -> AffineStorage {
-        AffineStorage {
-            x: FieldStorage::default(),
-            y: FieldStorage::default(),
-        }
+        AffineStorage::ZERO
     }
 }
 
@@ -67,6 +79,8 @@ pub static JACOBIAN_INFINITY: Jacobian = Jacobian {
     infinity: true,
 };
 
+pub static PROJECTIVE_INFINITY: Projective = Projective::INFINITY;
+
 pub static AFFINE_G: Affine = Affine::new(
     Field::new(
         0x79BE667E, 0xF9DCBBAC, 0x55A06295, 0xCE870B07, 0x029BFCDB, 0x2DCE28D9, 0x59F2815B,
@@ -79,6 +93,8 @@ pub static AFFINE_G: Affine = Affine::new(
 );
 
 pub const CURVE_B: u32 = 7;
+/// `3 * CURVE_B`, used by the complete addition formulas.
+const CURVE_B3: u32 = 3 * CURVE_B;
 
 impl Affine {
     /// Create a new affine.
@@ -130,7 +146,7 @@ impl Affine {
     }
 
     /// Check whether a group element is the point at infinity.
-    pub fn is_infinity(&self) -> bool {
+    pub const fn is_infinity(&self) -> bool {
         self.infinity
     }
 
@@ -161,6 +177,25 @@ impl Affine {
         ret
     }
 
+    /// Set a group element equal to `a + b`, given `dxi`, the inverse of
+    /// `b.x - a.x`. Both inputs must be finite with magnitudes at most 1,
+    /// and must have different X coordinates. Computing `dxi` for many
+    /// additions with a single inversion makes this cheaper than
+    /// `Jacobian::add_ge_var`. The output is normalized. Not constant time.
+    pub fn add_ge_dxinv_var(&mut self, a: &Affine, b: &Affine, dxi: &Field) {
+        debug_assert!(!a.is_infinity() && !b.is_infinity());
+
+        let lambda = (b.y + a.y.neg(1)) * *dxi;
+        let mut x = lambda.sqr() + a.x.neg(1) + b.x.neg(1);
+        x.normalize_var();
+        let mut y = lambda * (a.x + x.neg(1)) + a.y.neg(1);
+        y.normalize_var();
+
+        self.x = x;
+        self.y = y;
+        self.infinity = false;
+    }
+
     /// Set a group element equal to another which is given in
     /// jacobian coordinates.
     pub fn set_gej(&mut self, a: &Jacobian) {
@@ -182,6 +217,21 @@ impl Affine {
         ge
     }
 
+    /// Set a group element equal to another which is given in
+    /// projective coordinates. Constant time.
+    pub fn set_proj(&mut self, a: &Projective) {
+        let zi = a.z.inv();
+        self.x = a.x * zi;
+        self.y = a.y * zi;
+        self.infinity = a.is_infinity();
+    }
+
+    pub fn from_proj(a: &Projective) -> Self {
+        let mut ge = Self::default();
+        ge.set_proj(a);
+        ge
+    }
+
     pub fn set_gej_var(&mut self, a: &Jacobian) {
         let mut a = *a;
         self.infinity = a.infinity;
@@ -206,6 +256,17 @@ impl Affine {
         self.infinity = a.infinity;
     }
 
+    /// Convert to the compact storage form. The point must not be
+    /// infinity.
+    pub const fn to_storage(&self) -> AffineStorage {
+        debug_assert!(!self.is_infinity());
+        let mut x = self.x;
+        let mut y = self.y;
+        x.normalize();
+        y.normalize();
+        AffineStorage::new(x.to_storage(), y.to_storage())
+    }
+
     /// Clear a secp256k1_ge to prevent leaking sensitive information.
     pub fn clear(&mut self) {
         self.infinity = false;
@@ -256,6 +317,14 @@ pub fn globalz_set_table_gej(r: &mut [Affine], globalz: &mut Field, a: &[Jacobia
 }
 
 impl Jacobian {
+    /// All-zero coordinates, the same as `Jacobian::default`.
+    pub(crate) const ZERO: Jacobian = Jacobian {
+        x: Field::ZERO,
+        y: Field::ZERO,
+        z: Field::ZERO,
+        infinity: false,
+    };
+
     /// Create a new jacobian.
     pub const fn new(x: Field, y: Field) -> Self {
         Self {
@@ -267,24 +336,24 @@ impl Jacobian {
     }
 
     /// Set a group element (jacobian) equal to the point at infinity.
-    pub fn set_infinity(&mut self) {
+    pub const fn set_infinity(&mut self) {
         self.infinity = true;
-        self.x.clear();
-        self.y.clear();
-        self.z.clear();
+        self.x = Field::ZERO;
+        self.y = Field::ZERO;
+        self.z = Field::ZERO;
     }
 
     /// Set a group element (jacobian) equal to another which is given
     /// in affine coordinates.
-    pub fn set_ge(&mut self, a: &Affine) {
+    pub const fn set_ge(&mut self, a: &Affine) {
         self.infinity = a.infinity;
         self.x = a.x;
         self.y = a.y;
         self.z.set_int(1);
     }
 
-    pub fn from_ge(a: &Affine) -> Self {
-        let mut gej = Self::default();
+    pub const fn from_ge(a: &Affine) -> Self {
+        let mut gej = Jacobian::ZERO;
         gej.set_ge(a);
         gej
     }
@@ -301,7 +370,7 @@ impl Jacobian {
 
     /// Set r equal to the inverse of a (i.e., mirrored around the X
     /// axis).
-    pub fn neg_in_place(&mut self, a: &Jacobian) {
+    pub const fn neg_in_place(&mut self, a: &Jacobian) {
         self.infinity = a.infinity;
         self.x = a.x;
         self.y = a.y;
@@ -310,14 +379,14 @@ impl Jacobian {
         self.y = self.y.neg(1);
     }
 
-    pub fn neg(&self) -> Jacobian {
-        let mut ret = Jacobian::default();
+    pub const fn neg(&self) -> Jacobian {
+        let mut ret = Jacobian::ZERO;
         ret.neg_in_place(self);
         ret
     }
 
     /// Check whether a group element is the point at infinity.
-    pub fn is_infinity(&self) -> bool {
+    pub const fn is_infinity(&self) -> bool {
         self.infinity
     }
 
@@ -328,7 +397,7 @@ impl Jacobian {
         }
 
         let yz = self.y * self.z;
-        yz.is_quad_var()
+        yz.is_square()
     }
 
     /// Set r equal to the double of a. If rzr is not-NULL, r->z =
@@ -475,7 +544,8 @@ impl Jacobian {
         let mut m_alt = u2.neg(1);
         let tt = u1 * m_alt;
         rr += tt;
-        let degenerate = m.normalizes_to_zero() && rr.normalizes_to_zero();
+        let degenerate = Choice::from(m.normalizes_to_zero() as u8)
+            & Choice::from(rr.normalizes_to_zero() as u8);
         let mut rr_alt = s1;
         rr_alt.mul_int(2);
         m_alt += u1;
@@ -515,9 +585,10 @@ impl Jacobian {
         self.x.mul_int(4);
         self.y.mul_int(4);
 
-        self.x.cmov(&b.x, a.infinity);
-        self.y.cmov(&b.y, a.infinity);
-        self.z.cmov(&FE1, a.infinity);
+        let a_infinity = Choice::from(a.infinity as u8);
+        self.x.cmov(&b.x, a_infinity);
+        self.y.cmov(&b.y, a_infinity);
+        self.z.cmov(&FE1, a_infinity);
         self.infinity = infinity;
     }
 
@@ -683,6 +754,287 @@ impl Jacobian {
     }
 }
 
+impl Projective {
+    /// The point at infinity, the same as `Projective::default`.
+    pub(crate) const INFINITY: Projective = Projective {
+        x: Field::ZERO,
+        y: Field::from_int(1),
+        z: Field::ZERO,
+    };
+
+    /// Set a group element equal to another which is given in affine
+    /// coordinates. Constant time.
+    pub fn set_ge(&mut self, a: &Affine) {
+        self.x = a.x;
+        self.y = a.y;
+        self.x.normalize_weak();
+        self.y.normalize_weak();
+        self.z = Field::from_int(1);
+        self.cmov(&PROJECTIVE_INFINITY, Choice::from(a.infinity as u8));
+    }
+
+    pub fn from_ge(a: &Affine) -> Self {
+        let mut ret = Self::default();
+        ret.set_ge(a);
+        ret
+    }
+
+    /// Set a group element equal to another which is given in jacobian
+    /// coordinates. Constant time.
+    pub fn set_gej(&mut self, a: &Jacobian) {
+        self.x = a.x * a.z;
+        self.y = a.y;
+        self.y.normalize_weak();
+        self.z = a.z.sqr() * a.z;
+        self.cmov(&PROJECTIVE_INFINITY, Choice::from(a.infinity as u8));
+    }
+
+    pub fn from_gej(a: &Jacobian) -> Self {
+        let mut ret = Self::default();
+        ret.set_gej(a);
+        ret
+    }
+
+    /// Check whether a group element is the point at infinity. Constant
+    /// time.
+    pub fn is_infinity(&self) -> bool {
+        self.z.normalizes_to_zero()
+    }
+
+    pub const fn neg_in_place(&mut self, a: &Projective) {
+        *self = *a;
+        self.y.normalize_weak();
+        self.y = self.y.neg(1);
+        self.y.normalize_weak();
+    }
+
+    pub const fn neg(&self) -> Projective {
+        let mut ret = Projective::INFINITY;
+        ret.neg_in_place(self);
+        ret
+    }
+
+    /// If flag is true, set *r equal to *a; otherwise leave
+    /// it. Constant-time.
+    pub fn cmov(&mut self, a: &Projective, flag: Choice) {
+        self.x.cmov(&a.x, flag);
+        self.y.cmov(&a.y, flag);
+        self.z.cmov(&a.z, flag);
+    }
+
+    /// Set r equal to the double of a. Complete: valid for every input,
+    /// including infinity. Constant time. This is algorithm 9 of
+    /// Renes-Costello-Batina 2015, "Complete addition formulas for prime
+    /// order elliptic curves". Usable in const context, so it always uses
+    /// the portable field arithmetic. Requires the inputs' magnitudes to be
+    /// at most 1. The output magnitudes are 1.
+    pub const fn double_in_place(&mut self, a: &Projective) {
+        let mut t0 = a.y.sqr_const();
+        let mut z3 = t0;
+        z3.mul_int(8);
+        let mut t1 = a.y.mul_const(&a.z);
+        let mut t2 = a.z.sqr_const();
+        t2.mul_int(CURVE_B3);
+        t2.normalize_weak();
+        let mut x3 = t2.mul_const(&z3);
+        let mut y3 = t0;
+        y3.add_assign_const(&t2);
+        z3 = z3.mul_const(&t1);
+        t1 = t2;
+        t1.mul_int(2);
+        t2.add_assign_const(&t1);
+        t0.add_assign_const(&t2.neg(3));
+        y3 = y3.mul_const(&t0);
+        y3.add_assign_const(&x3);
+        t1 = a.x.mul_const(&a.y);
+        x3 = t0.mul_const(&t1);
+        x3.mul_int(2);
+
+        x3.normalize_weak();
+        y3.normalize_weak();
+        self.x = x3;
+        self.y = y3;
+        self.z = z3;
+    }
+
+    pub const fn double(&self) -> Projective {
+        let mut ret = Projective::INFINITY;
+        ret.double_in_place(self);
+        ret
+    }
+
+    /// Set r equal to the sum of a and b. Complete: valid for every input,
+    /// including doubling, infinity and a point plus its negation. Constant
+    /// time. This is algorithm 7 of Renes-Costello-Batina 2015. Usable in
+    /// const context, so it always uses the portable field arithmetic.
+    /// Requires the inputs' magnitudes to be at most 1. The output
+    /// magnitudes are 1.
+    pub const fn add_in_place(&mut self, a: &Projective, b: &Projective) {
+        let mut t0 = a.x.mul_const(&b.x);
+        let mut t1 = a.y.mul_const(&b.y);
+        let mut t2 = a.z.mul_const(&b.z);
+        let mut t3 = a.x;
+        t3.add_assign_const(&a.y);
+        let mut t4 = b.x;
+        t4.add_assign_const(&b.y);
+        t3 = t3.mul_const(&t4);
+        t4 = t0;
+        t4.add_assign_const(&t1);
+        t3.add_assign_const(&t4.neg(2));
+        t4 = a.y;
+        t4.add_assign_const(&a.z);
+        let mut x3 = b.y;
+        x3.add_assign_const(&b.z);
+        t4 = t4.mul_const(&x3);
+        x3 = t1;
+        x3.add_assign_const(&t2);
+        t4.add_assign_const(&x3.neg(2));
+        x3 = a.x;
+        x3.add_assign_const(&a.z);
+        let mut y3 = b.x;
+        y3.add_assign_const(&b.z);
+        x3 = x3.mul_const(&y3);
+        y3 = t0;
+        y3.add_assign_const(&t2);
+        y3 = y3.neg(2);
+        y3.add_assign_const(&x3);
+        x3 = t0;
+        x3.mul_int(2);
+        t0.add_assign_const(&x3);
+        t2.mul_int(CURVE_B3);
+        t2.normalize_weak();
+        let mut z3 = t1;
+        z3.add_assign_const(&t2);
+        t1.add_assign_const(&t2.neg(1));
+        y3.normalize_weak();
+        y3.mul_int(CURVE_B3);
+        y3.normalize_weak();
+        x3 = t4.mul_const(&y3);
+        t2 = t3.mul_const(&t1);
+        x3 = x3.neg(1);
+        x3.add_assign_const(&t2);
+        y3 = y3.mul_const(&t0);
+        t1 = t1.mul_const(&z3);
+        y3.add_assign_const(&t1);
+        t0 = t0.mul_const(&t3);
+        z3 = z3.mul_const(&t4);
+        z3.add_assign_const(&t0);
+
+        x3.normalize_weak();
+        y3.normalize_weak();
+        z3.normalize_weak();
+        self.x = x3;
+        self.y = y3;
+        self.z = z3;
+    }
+
+    pub const fn add(&self, b: &Projective) -> Projective {
+        let mut ret = Projective::INFINITY;
+        ret.add_in_place(self, b);
+        ret
+    }
+
+    /// Set r equal to the sum of a and b (with b given in affine
+    /// coordinates). Complete: valid for every input, including doubling,
+    /// infinity and a point plus its negation. Constant time. This is
+    /// algorithm 8 of Renes-Costello-Batina 2015, extended to an infinite
+    /// b. Requires the inputs' magnitudes to be at most 1. The output
+    /// magnitudes are 1.
+    pub fn add_ge_in_place(&mut self, a: &Projective, b: &Affine) {
+        let mut t0 = a.x * b.x;
+        let mut t1 = a.y * b.y;
+        let mut t3 = (b.x + b.y) * (a.x + a.y);
+        let mut t4 = t0 + t1;
+        t3 += t4.neg(2);
+        t4 = b.y * a.z;
+        t4 += a.y;
+        let mut y3 = b.x * a.z;
+        y3 += a.x;
+        let mut x3 = t0 + t0;
+        t0 += x3;
+        let mut t2 = a.z;
+        t2.mul_int(CURVE_B3);
+        t2.normalize_weak();
+        let mut z3 = t1 + t2;
+        t1 += t2.neg(1);
+        y3.normalize_weak();
+        y3.mul_int(CURVE_B3);
+        y3.normalize_weak();
+        x3 = t4 * y3;
+        t2 = t3 * t1;
+        x3 = t2 + x3.neg(1);
+        y3 *= t0;
+        t1 *= z3;
+        y3 += t1;
+        t0 *= t3;
+        z3 *= t4;
+        z3 += t0;
+
+        x3.normalize_weak();
+        y3.normalize_weak();
+        z3.normalize_weak();
+        self.x = x3;
+        self.y = y3;
+        self.z = z3;
+        self.cmov(a, Choice::from(b.infinity as u8));
+    }
+
+    pub fn add_ge(&self, b: &Affine) -> Projective {
+        let mut ret = Projective::default();
+        ret.add_ge_in_place(self, b);
+        ret
+    }
+
+    /// Clear a group element to prevent leaking sensitive information.
+    pub fn clear(&mut self) {
+        self.x.clear();
+        self.y.clear();
+        self.z.clear();
+    }
+}
+
+impl ConstantTimeEq for Affine {
+    /// Two points at infinity are equal regardless of their coordinates.
+    fn ct_eq(&self, other: &Affine) -> Choice {
+        let a_inf = Choice::from(self.infinity as u8);
+        let b_inf = Choice::from(other.infinity as u8);
+        let coords = self.x.ct_eq(&other.x) & self.y.ct_eq(&other.y);
+        (a_inf & b_inf) | (!a_inf & !b_inf & coords)
+    }
+}
+
+impl ConditionallySelectable for Affine {
+    fn conditional_select(a: &Affine, b: &Affine, choice: Choice) -> Affine {
+        Affine {
+            x: Field::conditional_select(&a.x, &b.x, choice),
+            y: Field::conditional_select(&a.y, &b.y, choice),
+            infinity: u8::conditional_select(&(a.infinity as u8), &(b.infinity as u8), choice) != 0,
+        }
+    }
+}
+
+impl Neg for &Affine {
+    type Output = Affine;
+    fn neg(self) -> Affine {
+        Affine::neg(self)
+    }
+}
+
+impl ConstantTimeEq for AffineStorage {
+    fn ct_eq(&self, other: &AffineStorage) -> Choice {
+        self.x.ct_eq(&other.x) & self.y.ct_eq(&other.y)
+    }
+}
+
+impl ConditionallySelectable for AffineStorage {
+    fn conditional_select(a: &AffineStorage, b: &AffineStorage, choice: Choice) -> AffineStorage {
+        AffineStorage {
+            x: FieldStorage::conditional_select(&a.x, &b.x, choice),
+            y: FieldStorage::conditional_select(&a.y, &b.y, choice),
+        }
+    }
+}
+
 impl From<AffineStorage> for Affine {
     fn from(a: AffineStorage) -> Affine {
         Affine::new(a.x.into(), a.y.into())
@@ -690,15 +1042,18 @@ impl From<AffineStorage> for Affine {
 }
 
 impl Into<AffineStorage> for Affine {
-    fn into(mut self) -> AffineStorage {
-        debug_assert!(!self.is_infinity());
-        self.x.normalize();
-        self.y.normalize();
-        AffineStorage::new(self.x.into(), self.y.into())
+    fn into(self) -> AffineStorage {
+        self.to_storage()
     }
 }
 
 impl AffineStorage {
+    /// All-zero coordinates, the same as `AffineStorage::default`.
+    pub const ZERO: AffineStorage = AffineStorage {
+        x: FieldStorage([0; 8]),
+        y: FieldStorage([0; 8]),
+    };
+
     /// Create a new affine storage.
     pub const fn new(x: FieldStorage, y: FieldStorage) -> Self {
         Self { x, y }
@@ -706,8 +1061,13 @@ impl AffineStorage {
 
     /// If flag is true, set *r equal to *a; otherwise leave
     /// it. Constant-time.
-    pub fn cmov(&mut self, a: &AffineStorage, flag: bool) {
-        self.x.cmov(&a.x, flag);
-        self.y.cmov(&a.y, flag);
+    pub fn cmov(&mut self, a: &AffineStorage, flag: Choice) {
+        self.conditional_assign(a, flag);
+    }
+
+    /// Clear a group element to prevent leaking sensitive information.
+    pub fn clear(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
     }
 }
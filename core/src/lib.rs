@@ -20,6 +20,7 @@
     unused_parens
 )]
 #![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[macro_use]
@@ -33,13 +34,15 @@ mod ecmult;
 mod error;
 mod scalar;
 
-pub use crate::error::Error;
+pub use crate::error::{
+    DerError, EncodingError, Error, ErrorDetail, ErrorKind, Input, VerifyError,
+};
 
 /// Curve related structs.
 pub mod curve {
     pub use crate::{
         field::{Field, FieldStorage},
-        group::{Affine, AffineStorage, Jacobian, AFFINE_G, CURVE_B},
+        group::{Affine, AffineStorage, Jacobian, Projective, AFFINE_G, CURVE_B},
         scalar::Scalar,
     };
 
@@ -64,10 +67,22 @@ pub mod util {
 
     pub use crate::{
         ecmult::{
-            odd_multiples_table, ECMULT_TABLE_SIZE_A, ECMULT_TABLE_SIZE_G, WINDOW_A, WINDOW_G,
+            ct_mul, inv_all_var_to, odd_multiples_table, odd_multiples_table_storage_const,
+            set_all_gej_var_to, ECMULT_GEN_COMB_BITS, ECMULT_GEN_COMB_BLOCKS,
+            ECMULT_GEN_COMB_POINTS, ECMULT_GEN_COMB_SPACING, ECMULT_GEN_COMB_TEETH,
+            ECMULT_TABLE_PARTS, ECMULT_TABLE_PART_SIZE, ECMULT_TABLE_SIZE_A, ECMULT_TABLE_SIZE_G,
+            WINDOW_A, WINDOW_G,
         },
-        group::{globalz_set_table_gej, set_table_gej_var, AFFINE_INFINITY, JACOBIAN_INFINITY},
+        group::{
+            globalz_set_table_gej, set_table_gej_var, AFFINE_INFINITY, JACOBIAN_INFINITY,
+            PROJECTIVE_INFINITY,
+        },
+    };
+
+    #[cfg(feature = "alloc")]
+    pub use crate::ecmult::{
+        ecmult_gen_compute_table, inv_all_var, odd_multiples_table_storage_var, set_all_gej_var,
     };
 
-    pub use crate::der::{Decoder, SignatureArray};
+    pub use crate::der::{check_der_strict, parse_der_strict, Decoder, SignatureArray};
 }
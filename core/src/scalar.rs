@@ -1,6 +1,7 @@
 use core::ops::{Add, AddAssign, Mul, MulAssign, Neg};
 use crunchy::unroll;
-use subtle::Choice;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
 
 const SECP256K1_N: [u32; 8] = [
     0xD0364141, 0xBFD25E8C, 0xAF48A03B, 0xBAAEDCE6, 0xFFFFFFFE, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF,
@@ -26,11 +27,10 @@ const SECP256K1_N_H_7: u32 = 0x7FFFFFFF;
 pub struct Scalar(pub [u32; 8]);
 
 impl Scalar {
-    /// Clear a scalar to prevent the leak of sensitive data.
+    /// Clear a scalar to prevent the leak of sensitive data. The same as
+    /// `zeroize`.
     pub fn clear(&mut self) {
-        unsafe {
-            core::ptr::write_volatile(&mut self.0, [0u32; 8]);
-        }
+        self.zeroize();
     }
 
     /// Set a scalar to an unsigned integer.
@@ -926,6 +926,28 @@ impl Scalar {
     }
 }
 
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ConstantTimeEq for Scalar {
+    fn ct_eq(&self, other: &Scalar) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for Scalar {
+    fn conditional_select(a: &Scalar, b: &Scalar, choice: Choice) -> Scalar {
+        let mut r = [0u32; 8];
+        for i in 0..8 {
+            r[i] = u32::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        Scalar(r)
+    }
+}
+
 impl Default for Scalar {
     fn default() -> Scalar {
         Scalar([0u32; 8])
@@ -1,20 +1,42 @@
-#![allow(clippy::needless_range_loop)]
-
-use libsecp256k1_core::curve::ECMultGenContext;
+use libsecp256k1_core::{
+    curve::AffineStorage,
+    util::{
+        ecmult_gen_compute_table, ECMULT_GEN_COMB_BLOCKS, ECMULT_GEN_COMB_SPACING,
+        ECMULT_GEN_COMB_TEETH,
+    },
+};
 use std::{
     fs::File,
     io::{Error, Write},
 };
 
 pub fn generate_to(file: &mut File) -> Result<(), Error> {
-    let context = ECMultGenContext::new_boxed();
-    let prec = context.inspect_raw().as_ref();
+    generate_comb_to(
+        file,
+        ECMULT_GEN_COMB_BLOCKS,
+        ECMULT_GEN_COMB_TEETH,
+        ECMULT_GEN_COMB_SPACING,
+    )
+}
+
+/// Write the multi-comb table for the given `blocks`, `teeth` and
+/// `spacing` as an array of `blocks` rows of `1 << (teeth - 1)` entries.
+/// Only the configuration selected by the `ecmult-gen-*` features can be
+/// loaded by `ECMultGenContext::new_from_raw`.
+pub fn generate_comb_to(
+    file: &mut File,
+    blocks: usize,
+    teeth: usize,
+    spacing: usize,
+) -> Result<(), Error> {
+    let points = 1 << (teeth - 1);
+    let mut prec = vec![AffineStorage::default(); blocks * points];
+    ecmult_gen_compute_table(&mut prec, blocks, teeth, spacing);
 
     file.write_fmt(format_args!("["))?;
-    for j in 0..64 {
+    for row in prec.chunks(points) {
         file.write_fmt(format_args!("    ["))?;
-        for i in 0..16 {
-            let pg = prec[j][i];
+        for pg in row {
             file.write_fmt(format_args!(
                 "        crate::curve::AffineStorage::new(crate::curve::FieldStorage::new({}, {}, {}, {}, {}, {}, {}, {}), crate::curve::FieldStorage::new({}, {}, {}, {}, {}, {}, {}, {})),",
                 pg.x.0[7], pg.x.0[6], pg.x.0[5], pg.x.0[4], pg.x.0[3], pg.x.0[2], pg.x.0[1], pg.x.0[0],
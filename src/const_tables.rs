@@ -0,0 +1,58 @@
+//! Tables of the `const-context` static contexts. The compiler limits the
+//! work done to evaluate a single constant, and warns about constants
+//! that take long, so each part of the tables is computed in its own
+//! constant and the contexts are assembled from them.
+
+use crate::{
+    curve::{AffineStorage, ECMultContext, ECMultGenContext},
+    util::{
+        ECMULT_GEN_COMB_BLOCKS, ECMULT_GEN_COMB_POINTS, ECMULT_TABLE_PARTS, ECMULT_TABLE_PART_SIZE,
+        ECMULT_TABLE_SIZE_G,
+    },
+};
+
+// The parts are listed below up to these counts, which cover the largest
+// tables.
+const _: () = assert!(ECMULT_TABLE_PARTS <= 256 && ECMULT_GEN_COMB_BLOCKS <= 64);
+
+struct TablePart<const K: usize>;
+
+impl<const K: usize> TablePart<K> {
+    const ENTRIES: [AffineStorage; ECMULT_TABLE_PART_SIZE] = ECMultContext::table_part_const(K);
+}
+
+struct TableBlock<const K: usize>;
+
+impl<const K: usize> TableBlock<K> {
+    const ENTRIES: [AffineStorage; ECMULT_GEN_COMB_POINTS] = ECMultGenContext::table_block_const(K);
+}
+
+/// Put the parts `$part::<K>` in place for `K` in `0..16 * n`, where the
+/// `$hi` are `0..n`. Parts past the end of the table are empty, and ignored.
+macro_rules! set_parts {
+    ($context:ident.$set:ident, $part:ident; $($hi:literal)*) => {
+        $( set_parts!(@ $context.$set, $part, $hi; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15); )*
+    };
+    (@ $context:ident.$set:ident, $part:ident, $hi:literal; $($lo:literal)*) => {
+        $( $context.$set($hi * 16 + $lo, &$part::<{ $hi * 16 + $lo }>::ENTRIES); )*
+    };
+}
+
+pub const ECMULT_CONTEXT: ECMultContext = {
+    // Every entry is overwritten by a part.
+    let mut context =
+        unsafe { ECMultContext::new_from_raw([AffineStorage::ZERO; ECMULT_TABLE_SIZE_G]) };
+    set_parts!(context.set_table_part_const, TablePart; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15);
+    context
+};
+
+pub const ECMULT_GEN_CONTEXT: ECMultGenContext = {
+    // Every block is overwritten.
+    let mut context = unsafe {
+        ECMultGenContext::new_from_raw(
+            [[AffineStorage::ZERO; ECMULT_GEN_COMB_POINTS]; ECMULT_GEN_COMB_BLOCKS],
+        )
+    };
+    set_parts!(context.set_table_block_const, TableBlock; 0 1 2 3);
+    context
+};
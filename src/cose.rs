@@ -0,0 +1,641 @@
+//! ES256K support for COSE (RFC 8812, RFC 9052): CBOR encodings of
+//! secp256k1 keys as COSE_Key, and COSE_Sign1 messages signed with ECDSA
+//! over the SHA-256 hash of their Sig_structure. Available with the `cose`
+//! feature. Parsing keys and verifying messages do not allocate.
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    curve::{ECMultContext, ECMultGenContext},
+    keys, util, verify_detailed_with_context, EncodingError, Error, ErrorDetail, ErrorKind, Input,
+    Message, PublicKey, SecretKey, Signature, VerifyOptions,
+};
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+use crate::{ECMULT_CONTEXT, ECMULT_GEN_CONTEXT};
+
+pub const COSE_PUBLIC_KEY_SIZE: usize = 75;
+pub const COSE_SECRET_KEY_SIZE: usize = 110;
+
+const FORMAT: &str = "COSE";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why a COSE_Key or a COSE_Sign1 message is rejected. Errors carry it as
+/// an `ErrorDetail::Encoding`, from which `CoseError::try_from` gets it
+/// back.
+#[non_exhaustive]
+pub enum CoseError {
+    /// The input is not a single well-formed CBOR item with definite
+    /// lengths.
+    Cbor,
+    /// The CBOR item does not have the structure of a COSE_Key or a
+    /// COSE_Sign1, or a label appears twice.
+    Structure,
+    /// The algorithm is missing or is not ES256K (-47).
+    UnsupportedAlgorithm,
+    /// The header lists critical parameters, none of which are supported.
+    CriticalHeader,
+    /// The key type is not EC2.
+    KeyType,
+    /// The curve is not secp256k1 (8).
+    Curve,
+    /// A coordinate or the secret is missing or is not 32 bytes.
+    CoordinateLength,
+    /// The private key `d` is missing.
+    MissingSecret,
+}
+
+impl CoseError {
+    const ALL: [CoseError; 8] = [
+        CoseError::Cbor,
+        CoseError::Structure,
+        CoseError::UnsupportedAlgorithm,
+        CoseError::CriticalHeader,
+        CoseError::KeyType,
+        CoseError::Curve,
+        CoseError::CoordinateLength,
+        CoseError::MissingSecret,
+    ];
+
+    fn message(self) -> &'static str {
+        match self {
+            CoseError::Cbor => "Invalid CBOR",
+            CoseError::Structure => "Invalid COSE structure",
+            CoseError::UnsupportedAlgorithm => "Unsupported algorithm",
+            CoseError::CriticalHeader => "Unsupported critical header",
+            CoseError::KeyType => "Key type is not EC2",
+            CoseError::Curve => "Curve is not secp256k1",
+            CoseError::CoordinateLength => "Invalid coordinate length",
+            CoseError::MissingSecret => "Missing private key",
+        }
+    }
+}
+
+impl From<CoseError> for ErrorDetail {
+    fn from(err: CoseError) -> ErrorDetail {
+        ErrorDetail::Encoding(EncodingError::new(FORMAT, err as u8, err.message()))
+    }
+}
+
+impl TryFrom<ErrorDetail> for CoseError {
+    type Error = ErrorDetail;
+
+    fn try_from(detail: ErrorDetail) -> Result<CoseError, ErrorDetail> {
+        match detail {
+            ErrorDetail::Encoding(err) if err.format() == FORMAT => CoseError::ALL
+                .get(usize::from(err.code()))
+                .copied()
+                .ok_or(detail),
+            _ => Err(detail),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CoseError {}
+
+impl core::fmt::Display for CoseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// Key type EC2.
+const KTY_EC2: i64 = 2;
+/// Curve secp256k1, from RFC 8812.
+const CRV_SECP256K1: i64 = 8;
+/// Algorithm ES256K, from RFC 8812.
+const ALG_ES256K: i64 = -47;
+
+const KEY_KTY: i64 = 1;
+const KEY_ALG: i64 = 3;
+const KEY_CRV: i64 = -1;
+const KEY_X: i64 = -2;
+const KEY_Y: i64 = -3;
+const KEY_D: i64 = -4;
+
+const HEADER_ALG: i64 = 1;
+const HEADER_CRIT: i64 = 2;
+
+/// CBOR tag 18 of COSE_Sign1, as a single byte.
+const TAG_COSE_SIGN1: u8 = 0xd2;
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+/// The protected header of the messages made by `sign_cose_sign1`, the
+/// map `{1: -47}`.
+const PROTECTED_ES256K: [u8; 4] = [0xa1, 0x01, 0x38, 0x2e];
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NINT: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+/// The nesting allowed when skipping unknown items, so that hostile input
+/// cannot exhaust the stack.
+const MAX_DEPTH: usize = 16;
+
+/// The shortest head of a CBOR item, and its length.
+fn head(major: u8, value: u64) -> ([u8; 9], usize) {
+    let mut ret = [0u8; 9];
+    let len = if value < 24 {
+        ret[0] = (major << 5) | value as u8;
+        1
+    } else if value <= 0xff {
+        ret[0] = (major << 5) | 24;
+        ret[1] = value as u8;
+        2
+    } else if value <= 0xffff {
+        ret[0] = (major << 5) | 25;
+        ret[1..3].copy_from_slice(&(value as u16).to_be_bytes());
+        3
+    } else if value <= 0xffff_ffff {
+        ret[0] = (major << 5) | 26;
+        ret[1..5].copy_from_slice(&(value as u32).to_be_bytes());
+        5
+    } else {
+        ret[0] = (major << 5) | 27;
+        ret[1..9].copy_from_slice(&value.to_be_bytes());
+        9
+    };
+    (ret, len)
+}
+
+/// A reader of CBOR items with definite lengths.
+struct Reader<'a>(&'a [u8], usize);
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader(data, 0)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.0.get(self.1).copied()
+    }
+
+    fn read_slice(&mut self, len: u64) -> Result<&'a [u8], CoseError> {
+        if len > (self.0.len() - self.1) as u64 {
+            return Err(CoseError::Cbor);
+        }
+        let v = &self.0[self.1..(self.1 + len as usize)];
+        self.1 += len as usize;
+        Ok(v)
+    }
+
+    fn read_head(&mut self) -> Result<(u8, u64), CoseError> {
+        let initial = self.read_slice(1)?[0];
+        let value = match initial & 0x1f {
+            info @ 0..=23 => info as u64,
+            24 => self.read_slice(1)?[0] as u64,
+            25 => self
+                .read_slice(2)?
+                .iter()
+                .fold(0, |acc, b| (acc << 8) | *b as u64),
+            26 => self
+                .read_slice(4)?
+                .iter()
+                .fold(0, |acc, b| (acc << 8) | *b as u64),
+            27 => self
+                .read_slice(8)?
+                .iter()
+                .fold(0, |acc, b| (acc << 8) | *b as u64),
+            // Reserved, or an indefinite length.
+            _ => return Err(CoseError::Cbor),
+        };
+        Ok((initial >> 5, value))
+    }
+
+    fn read_int(&mut self) -> Result<i64, CoseError> {
+        match self.read_head()? {
+            (MAJOR_UINT, value) if value <= i64::MAX as u64 => Ok(value as i64),
+            (MAJOR_NINT, value) if value <= i64::MAX as u64 => Ok(-1 - value as i64),
+            _ => Err(CoseError::Structure),
+        }
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], CoseError> {
+        match self.read_head()? {
+            (MAJOR_BYTES, len) => self.read_slice(len),
+            _ => Err(CoseError::Structure),
+        }
+    }
+
+    fn read_container(&mut self, major: u8) -> Result<u64, CoseError> {
+        match self.read_head()? {
+            (m, len) if m == major => Ok(len),
+            _ => Err(CoseError::Structure),
+        }
+    }
+
+    /// Read a map label, which is `None` for a text label.
+    fn read_label(&mut self) -> Result<Option<i64>, CoseError> {
+        match self.peek().map(|initial| initial >> 5) {
+            Some(MAJOR_UINT) | Some(MAJOR_NINT) => self.read_int().map(Some),
+            Some(MAJOR_TEXT) => self.skip(0).map(|_| None),
+            _ => Err(CoseError::Structure),
+        }
+    }
+
+    fn skip(&mut self, depth: usize) -> Result<(), CoseError> {
+        if depth > MAX_DEPTH {
+            return Err(CoseError::Cbor);
+        }
+
+        let (major, value) = self.read_head()?;
+        match major {
+            MAJOR_BYTES | MAJOR_TEXT => self.read_slice(value).map(|_| ()),
+            MAJOR_ARRAY | MAJOR_MAP => {
+                let items = if major == MAJOR_MAP {
+                    value.checked_mul(2).ok_or(CoseError::Cbor)?
+                } else {
+                    value
+                };
+                // Each item takes at least one byte, so this stops as soon
+                // as the input runs out.
+                for _ in 0..items {
+                    self.skip(depth + 1)?;
+                }
+                Ok(())
+            }
+            MAJOR_TAG => self.skip(depth + 1),
+            // Integers and simple values are entirely in the head.
+            _ => Ok(()),
+        }
+    }
+
+    fn finish(&self) -> Result<(), CoseError> {
+        if self.1 == self.0.len() {
+            Ok(())
+        } else {
+            Err(CoseError::Cbor)
+        }
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T) -> Result<(), CoseError> {
+    if slot.is_some() {
+        return Err(CoseError::Structure);
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// The `y` member of an EC2 key, which is the sign bit of a compressed key.
+enum Y<'a> {
+    Coordinate(&'a [u8]),
+    Odd(bool),
+}
+
+/// The members of a COSE_Key of secp256k1, whose type, curve and
+/// algorithm have been checked.
+struct Key<'a> {
+    x: Option<&'a [u8]>,
+    y: Option<Y<'a>>,
+    d: Option<&'a [u8]>,
+}
+
+fn read_key(p: &[u8]) -> Result<Key<'_>, CoseError> {
+    let mut reader = Reader::new(p);
+    let (mut kty, mut crv, mut alg) = (None, None, None);
+    let mut key = Key {
+        x: None,
+        y: None,
+        d: None,
+    };
+
+    for _ in 0..reader.read_container(MAJOR_MAP)? {
+        match reader.read_label()? {
+            Some(KEY_KTY) => set_once(&mut kty, reader.read_int()?)?,
+            Some(KEY_ALG) => set_once(&mut alg, reader.read_int()?)?,
+            Some(KEY_CRV) => set_once(&mut crv, reader.read_int()?)?,
+            Some(KEY_X) => set_once(&mut key.x, reader.read_bytes()?)?,
+            Some(KEY_Y) => {
+                let y = match reader.peek() {
+                    Some(0xf4) | Some(0xf5) => Y::Odd(reader.read_head()?.1 == 21),
+                    _ => Y::Coordinate(reader.read_bytes()?),
+                };
+                set_once(&mut key.y, y)?
+            }
+            Some(KEY_D) => set_once(&mut key.d, reader.read_bytes()?)?,
+            _ => reader.skip(0)?,
+        }
+    }
+    reader.finish()?;
+
+    if kty != Some(KTY_EC2) {
+        return Err(CoseError::KeyType);
+    }
+    if crv != Some(CRV_SECP256K1) {
+        return Err(CoseError::Curve);
+    }
+    if alg.is_some() && alg != Some(ALG_ES256K) {
+        return Err(CoseError::UnsupportedAlgorithm);
+    }
+    Ok(key)
+}
+
+fn key_error(kind: ErrorKind, err: CoseError) -> Error {
+    Error::new(kind).with_detail(err.into())
+}
+
+fn message_error(err: CoseError) -> Error {
+    Error::new(ErrorKind::InvalidSignature)
+        .with_input(Input::Token)
+        .with_detail(err.into())
+}
+
+fn parse_public_key(key: &Key) -> Result<PublicKey, Error> {
+    let error = |err| key_error(ErrorKind::InvalidPublicKey, err);
+    let x = match key.x {
+        Some(x) if x.len() == 32 => x,
+        _ => return Err(error(CoseError::CoordinateLength)),
+    };
+
+    match key.y {
+        Some(Y::Coordinate(y)) if y.len() == 32 => {
+            let mut full = [0u8; util::FULL_PUBLIC_KEY_SIZE];
+            full[0] = util::TAG_PUBKEY_FULL;
+            full[1..33].copy_from_slice(x);
+            full[33..].copy_from_slice(y);
+            PublicKey::parse(&full)
+        }
+        Some(Y::Odd(odd)) => {
+            let mut compressed = [0u8; util::COMPRESSED_PUBLIC_KEY_SIZE];
+            compressed[0] = if odd {
+                util::TAG_PUBKEY_ODD
+            } else {
+                util::TAG_PUBKEY_EVEN
+            };
+            compressed[1..].copy_from_slice(x);
+            PublicKey::parse_compressed(&compressed)
+        }
+        _ => Err(error(CoseError::CoordinateLength)),
+    }
+}
+
+/// Encode the public members of a COSE_Key, in the deterministic order of
+/// RFC 8949, in a map of `len` members.
+fn write_public_key(out: &mut [u8], pubkey: &PublicKey, len: u8) {
+    let full = pubkey.serialize();
+    out[0] = (MAJOR_MAP << 5) | len;
+    out[1..8].copy_from_slice(&[0x01, 0x02, 0x20, 0x08, 0x21, 0x58, 0x20]);
+    out[8..40].copy_from_slice(&full[1..33]);
+    out[40..43].copy_from_slice(&[0x22, 0x58, 0x20]);
+    out[43..75].copy_from_slice(&full[33..]);
+}
+
+impl PublicKey {
+    /// Parse a COSE_Key of type EC2 and curve secp256k1, with a full or
+    /// compressed point. Its algorithm, if given, must be ES256K, and its
+    /// private key `d`, if any, is ignored.
+    pub fn from_cose_key(p: &[u8]) -> Result<PublicKey, Error> {
+        let key = read_key(p).map_err(|e| key_error(ErrorKind::InvalidPublicKey, e))?;
+        parse_public_key(&key)
+    }
+
+    /// Encode as a COSE_Key of type EC2 and curve secp256k1, with the full
+    /// point.
+    pub fn to_cose_key(&self) -> [u8; COSE_PUBLIC_KEY_SIZE] {
+        let mut ret = [0u8; COSE_PUBLIC_KEY_SIZE];
+        write_public_key(&mut ret, self, 4);
+        ret
+    }
+}
+
+impl SecretKey {
+    /// Parse a COSE_Key of type EC2 and curve secp256k1 with its private
+    /// key `d`. Its public members, if given, must be the public key of
+    /// `d`, which is checked using the given context, or
+    /// `ErrorDetail::KeyMismatch` is returned.
+    pub fn from_cose_key_with_context(
+        p: &[u8],
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        let error = |err| key_error(ErrorKind::InvalidSecretKey, err);
+        let key = read_key(p).map_err(error)?;
+        let pubkey = if key.x.is_some() || key.y.is_some() {
+            let pubkey = parse_public_key(&key)
+                .map_err(|e| Error::new(ErrorKind::InvalidSecretKey).with_detail(e.detail()))?;
+            Some(pubkey)
+        } else {
+            None
+        };
+
+        let seckey = match key.d {
+            Some(d) if d.len() == util::SECRET_KEY_SIZE => Self::parse_slice(d)?,
+            Some(_) => return Err(error(CoseError::CoordinateLength)),
+            None => return Err(error(CoseError::MissingSecret)),
+        };
+        if let Some(pubkey) = pubkey {
+            keys::check_public_key(&seckey, &pubkey, context)?;
+        }
+        Ok(seckey)
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Parse a COSE_Key with its private key. See
+    /// `from_cose_key_with_context`.
+    pub fn from_cose_key(p: &[u8]) -> Result<SecretKey, Error> {
+        Self::from_cose_key_with_context(p, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Encode as a COSE_Key of type EC2 and curve secp256k1, with the
+    /// private key and the public key computed using the given context.
+    pub fn to_cose_key_with_context(
+        &self,
+        context: &ECMultGenContext,
+    ) -> [u8; COSE_SECRET_KEY_SIZE] {
+        let pubkey = PublicKey::from_secret_key_with_context(self, context);
+        let mut ret = [0u8; COSE_SECRET_KEY_SIZE];
+        write_public_key(&mut ret, &pubkey, 5);
+        ret[75..78].copy_from_slice(&[0x23, 0x58, 0x20]);
+        self.0
+            .fill_b32(arrayref::array_mut_ref!(ret, 78, util::SECRET_KEY_SIZE));
+        ret
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Encode as a COSE_Key of type EC2 and curve secp256k1, with the
+    /// private key.
+    pub fn to_cose_key(&self) -> [u8; COSE_SECRET_KEY_SIZE] {
+        self.to_cose_key_with_context(&ECMULT_GEN_CONTEXT)
+    }
+}
+
+/// Hash the Sig_structure of a COSE_Sign1, `["Signature1", protected,
+/// external_aad, payload]`, without building it in memory.
+fn sig_structure_hash(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Message {
+    let mut hasher = Sha256::new();
+    hasher.update([(MAJOR_ARRAY << 5) | 4]);
+    hasher.update(b"\x6aSignature1");
+    for item in [protected, external_aad, payload] {
+        let (head, len) = head(MAJOR_BYTES, item.len() as u64);
+        hasher.update(&head[..len]);
+        hasher.update(item);
+    }
+    Message::parse(&hasher.finalize().into())
+}
+
+fn read_header(
+    reader: &mut Reader,
+    alg: &mut Option<i64>,
+    crit: &mut bool,
+) -> Result<(), CoseError> {
+    for _ in 0..reader.read_container(MAJOR_MAP)? {
+        match reader.read_label()? {
+            Some(HEADER_ALG) => set_once(alg, reader.read_int()?)?,
+            Some(HEADER_CRIT) => {
+                *crit = true;
+                reader.skip(0)?
+            }
+            _ => reader.skip(0)?,
+        }
+    }
+    Ok(())
+}
+
+/// The members of a COSE_Sign1, whose headers have been checked.
+struct Sign1<'a> {
+    protected: &'a [u8],
+    payload: &'a [u8],
+    signature: &'a [u8],
+}
+
+/// Read a COSE_Sign1, tagged or not.
+fn read_sign1(p: &[u8]) -> Result<Sign1<'_>, CoseError> {
+    let mut reader = Reader::new(p);
+    if reader.peek() == Some(TAG_COSE_SIGN1) {
+        reader.read_head()?;
+    }
+    if reader.read_container(MAJOR_ARRAY)? != 4 {
+        return Err(CoseError::Structure);
+    }
+
+    let (mut alg, mut crit) = (None, false);
+    let protected = reader.read_bytes()?;
+    // An empty protected header is encoded as an empty byte string.
+    if !protected.is_empty() {
+        let mut header = Reader::new(protected);
+        read_header(&mut header, &mut alg, &mut crit)?;
+        header.finish()?;
+    }
+    read_header(&mut reader, &mut alg, &mut crit)?;
+    // A detached payload is `nil`, and is not supported.
+    let payload = reader.read_bytes()?;
+    let signature = reader.read_bytes()?;
+    reader.finish()?;
+
+    if crit {
+        return Err(CoseError::CriticalHeader);
+    }
+    if alg != Some(ALG_ES256K) {
+        return Err(CoseError::UnsupportedAlgorithm);
+    }
+    Ok(Sign1 {
+        protected,
+        payload,
+        signature,
+    })
+}
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    let (head, len) = head(MAJOR_BYTES, bytes.len() as u64);
+    out.extend_from_slice(&head[..len]);
+    out.extend_from_slice(bytes);
+}
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+/// Sign `payload` as a tagged COSE_Sign1 with the ES256K algorithm, using
+/// the given context. The protected header is `{1: -47}`, the unprotected
+/// header is empty, and `external_aad` is signed but not included.
+pub fn sign_cose_sign1_with_context(
+    payload: &[u8],
+    external_aad: &[u8],
+    seckey: &SecretKey,
+    context: &ECMultGenContext,
+) -> Vec<u8> {
+    let message = sig_structure_hash(&PROTECTED_ES256K, external_aad, payload);
+    let (signature, _) = crate::sign_with_context(&message, seckey, context);
+
+    let mut ret = Vec::with_capacity(payload.len() + 83);
+    ret.extend_from_slice(&[TAG_COSE_SIGN1, (MAJOR_ARRAY << 5) | 4]);
+    push_bytes(&mut ret, &PROTECTED_ES256K);
+    ret.push(MAJOR_MAP << 5);
+    push_bytes(&mut ret, payload);
+    // A COSE signature is `r || s`, the layout of `Signature::serialize`.
+    push_bytes(&mut ret, &signature.serialize());
+    ret
+}
+
+#[cfg(all(
+    feature = "hmac",
+    feature = "alloc",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Sign `payload` as a tagged COSE_Sign1 with the ES256K algorithm. See
+/// `sign_cose_sign1_with_context`.
+pub fn sign_cose_sign1(payload: &[u8], external_aad: &[u8], seckey: &SecretKey) -> Vec<u8> {
+    sign_cose_sign1_with_context(payload, external_aad, seckey, &ECMULT_GEN_CONTEXT)
+}
+
+/// Verify a COSE_Sign1, tagged or not, with the ES256K algorithm against
+/// the public key, using the given context, and return its payload.
+/// Messages with any other algorithm, with critical header parameters or
+/// with a detached payload are rejected.
+pub fn verify_cose_sign1_with_context<'a>(
+    cose_sign1: &'a [u8],
+    external_aad: &[u8],
+    pubkey: &PublicKey,
+    context: &ECMultContext,
+) -> Result<&'a [u8], Error> {
+    let sign1 = read_sign1(cose_sign1).map_err(message_error)?;
+    // A COSE signature is `r || s`, the layout of `Signature::parse_standard`.
+    let signature = Signature::parse_standard_slice(sign1.signature)?;
+
+    let message = sig_structure_hash(sign1.protected, external_aad, sign1.payload);
+    verify_detailed_with_context(
+        &message,
+        &signature,
+        pubkey,
+        VerifyOptions::default(),
+        context,
+    )?;
+
+    Ok(sign1.payload)
+}
+
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+/// Verify a COSE_Sign1 with the ES256K algorithm against the public key,
+/// and return its payload. See `verify_cose_sign1_with_context`.
+pub fn verify_cose_sign1<'a>(
+    cose_sign1: &'a [u8],
+    external_aad: &[u8],
+    pubkey: &PublicKey,
+) -> Result<&'a [u8], Error> {
+    verify_cose_sign1_with_context(cose_sign1, external_aad, pubkey, &ECMULT_CONTEXT)
+}
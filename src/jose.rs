@@ -0,0 +1,382 @@
+//! ES256K support for JOSE (RFC 8812): JSON Web Keys of secp256k1 keys
+//! with their RFC 7638 thumbprints, and compact JSON Web Signatures using
+//! ECDSA over SHA-256. Available with the `jose` feature.
+
+use alloc::{format, string::String, vec::Vec};
+use base64::{engine::Engine as _, prelude::BASE64_URL_SAFE_NO_PAD};
+use core::convert::TryFrom;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::{
+    curve::{ECMultContext, ECMultGenContext},
+    keys, util, verify_detailed_with_context, EncodingError, Error, ErrorDetail, ErrorKind, Input,
+    Message, PublicKey, SecretKey, Signature, VerifyOptions,
+};
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+use crate::{ECMULT_CONTEXT, ECMULT_GEN_CONTEXT};
+
+const KTY_EC: &str = "EC";
+const CRV_SECP256K1: &str = "secp256k1";
+const ALG_ES256K: &str = "ES256K";
+const FORMAT: &str = "JOSE";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why a JSON Web Key or a JSON Web Signature is rejected. Errors carry it
+/// as an `ErrorDetail::Encoding`, from which `JoseError::try_from` gets it
+/// back.
+#[non_exhaustive]
+pub enum JoseError {
+    /// The compact serialization does not have three parts.
+    Malformed,
+    /// A part or member is not unpadded base64url.
+    Base64,
+    /// The header is not a JSON object with the expected members.
+    Json,
+    /// The algorithm is not `ES256K`.
+    UnsupportedAlgorithm,
+    /// The header lists critical extensions, none of which are supported.
+    CriticalHeader,
+    /// The key type is not `EC`.
+    KeyType,
+    /// The curve is not `secp256k1`.
+    Curve,
+    /// A coordinate or the secret is not 32 bytes.
+    CoordinateLength,
+    /// The private key member `d` is missing.
+    MissingSecret,
+}
+
+impl JoseError {
+    const ALL: [JoseError; 9] = [
+        JoseError::Malformed,
+        JoseError::Base64,
+        JoseError::Json,
+        JoseError::UnsupportedAlgorithm,
+        JoseError::CriticalHeader,
+        JoseError::KeyType,
+        JoseError::Curve,
+        JoseError::CoordinateLength,
+        JoseError::MissingSecret,
+    ];
+
+    fn message(self) -> &'static str {
+        match self {
+            JoseError::Malformed => "Malformed compact serialization",
+            JoseError::Base64 => "Invalid base64url",
+            JoseError::Json => "Invalid JOSE header",
+            JoseError::UnsupportedAlgorithm => "Unsupported algorithm",
+            JoseError::CriticalHeader => "Unsupported critical header",
+            JoseError::KeyType => "Key type is not EC",
+            JoseError::Curve => "Curve is not secp256k1",
+            JoseError::CoordinateLength => "Invalid coordinate length",
+            JoseError::MissingSecret => "Missing private key",
+        }
+    }
+}
+
+impl From<JoseError> for ErrorDetail {
+    fn from(err: JoseError) -> ErrorDetail {
+        ErrorDetail::Encoding(EncodingError::new(FORMAT, err as u8, err.message()))
+    }
+}
+
+impl TryFrom<ErrorDetail> for JoseError {
+    type Error = ErrorDetail;
+
+    fn try_from(detail: ErrorDetail) -> Result<JoseError, ErrorDetail> {
+        match detail {
+            ErrorDetail::Encoding(err) if err.format() == FORMAT => JoseError::ALL
+                .get(usize::from(err.code()))
+                .copied()
+                .ok_or(detail),
+            _ => Err(detail),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JoseError {}
+
+impl core::fmt::Display for JoseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+/// A JSON Web Key of a secp256k1 key, with the members defined for `EC`
+/// keys. Other members are ignored when deserializing. The private key
+/// `d`, if any, is zeroized on drop and not shown by `Debug`.
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+}
+
+impl Jwk {
+    fn check_curve(&self) -> Result<(), JoseError> {
+        if self.kty != KTY_EC {
+            return Err(JoseError::KeyType);
+        }
+        if self.crv != CRV_SECP256K1 {
+            return Err(JoseError::Curve);
+        }
+        Ok(())
+    }
+
+    /// The RFC 7638 thumbprint of the key, base64url-encoded, as commonly
+    /// used for `kid`. It only covers the public members, so the public and
+    /// private JWKs of a key have the same thumbprint.
+    pub fn thumbprint(&self) -> String {
+        // Members in lexicographic order, without whitespace.
+        let json = |value: &String| serde_json::to_string(value).expect("strings serialize");
+        let canonical = format!(
+            "{{\"crv\":{},\"kty\":{},\"x\":{},\"y\":{}}}",
+            json(&self.crv),
+            json(&self.kty),
+            json(&self.x),
+            json(&self.y)
+        );
+        BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+    }
+}
+
+impl core::fmt::Debug for Jwk {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.kty)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &self.d.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Drop for Jwk {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+fn decode_32(value: &str) -> Result<[u8; 32], JoseError> {
+    let mut bytes = BASE64_URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| JoseError::Base64)?;
+    let ret = if bytes.len() == 32 {
+        let mut ret = [0u8; 32];
+        ret.copy_from_slice(&bytes);
+        Ok(ret)
+    } else {
+        Err(JoseError::CoordinateLength)
+    };
+    bytes.zeroize();
+    ret
+}
+
+fn key_error(kind: ErrorKind, err: JoseError) -> Error {
+    Error::new(kind).with_detail(err.into())
+}
+
+fn token_error(err: JoseError) -> Error {
+    Error::new(ErrorKind::InvalidSignature)
+        .with_input(Input::Token)
+        .with_detail(err.into())
+}
+
+impl PublicKey {
+    /// Parse a public JWK. Its private key `d`, if any, is ignored.
+    pub fn from_jwk(jwk: &Jwk) -> Result<PublicKey, Error> {
+        let error = |err| key_error(ErrorKind::InvalidPublicKey, err);
+        jwk.check_curve().map_err(error)?;
+
+        let mut full = [0u8; util::FULL_PUBLIC_KEY_SIZE];
+        full[0] = util::TAG_PUBKEY_FULL;
+        full[1..33].copy_from_slice(&decode_32(&jwk.x).map_err(error)?);
+        full[33..].copy_from_slice(&decode_32(&jwk.y).map_err(error)?);
+        Self::parse(&full)
+    }
+
+    pub fn to_jwk(&self) -> Jwk {
+        let full = self.serialize();
+        Jwk {
+            kty: KTY_EC.into(),
+            crv: CRV_SECP256K1.into(),
+            x: BASE64_URL_SAFE_NO_PAD.encode(&full[1..33]),
+            y: BASE64_URL_SAFE_NO_PAD.encode(&full[33..]),
+            d: None,
+        }
+    }
+
+    /// The RFC 7638 thumbprint of the JWK of the key. See `Jwk::thumbprint`.
+    pub fn jwk_thumbprint(&self) -> String {
+        self.to_jwk().thumbprint()
+    }
+}
+
+impl SecretKey {
+    /// Parse a private JWK. Its public members must be the public key of
+    /// `d`, which is checked using the given context, or
+    /// `ErrorDetail::KeyMismatch` is returned.
+    pub fn from_jwk_with_context(
+        jwk: &Jwk,
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        let pubkey = PublicKey::from_jwk(jwk)
+            .map_err(|e| Error::new(ErrorKind::InvalidSecretKey).with_detail(e.detail()))?;
+
+        let error = |err| key_error(ErrorKind::InvalidSecretKey, err);
+        let d = jwk
+            .d
+            .as_ref()
+            .ok_or(JoseError::MissingSecret)
+            .map_err(error)?;
+        let mut bytes = decode_32(d).map_err(error)?;
+        let seckey = Self::parse(&bytes);
+        bytes.zeroize();
+        let seckey = seckey?;
+        keys::check_public_key(&seckey, &pubkey, context)?;
+        Ok(seckey)
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Parse a private JWK. See `from_jwk_with_context`.
+    pub fn from_jwk(jwk: &Jwk) -> Result<SecretKey, Error> {
+        Self::from_jwk_with_context(jwk, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Get the private JWK of the key, with its public key computed using
+    /// the given context.
+    pub fn to_jwk_with_context(&self, context: &ECMultGenContext) -> Jwk {
+        let mut jwk = PublicKey::from_secret_key_with_context(self, context).to_jwk();
+        let mut bytes = self.serialize();
+        jwk.d = Some(BASE64_URL_SAFE_NO_PAD.encode(bytes));
+        bytes.zeroize();
+        jwk
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Get the private JWK of the key.
+    pub fn to_jwk(&self) -> Jwk {
+        self.to_jwk_with_context(&ECMULT_GEN_CONTEXT)
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    crit: Option<IgnoredAny>,
+}
+
+fn signing_input_message(signing_input: &str) -> Message {
+    Message::parse(&Sha256::digest(signing_input.as_bytes()).into())
+}
+
+#[cfg(feature = "hmac")]
+/// Sign `payload` as a compact JWS with the `ES256K` algorithm, using the
+/// given context. The protected header is `{"alg":"ES256K"}`, and the
+/// signature is the 64-byte `r || s` of a deterministic, low-S signature.
+pub fn sign_jws_with_context(
+    payload: &[u8],
+    seckey: &SecretKey,
+    context: &ECMultGenContext,
+) -> String {
+    let mut token = format!(
+        "{}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(format!("{{\"alg\":\"{}\"}}", ALG_ES256K)),
+        BASE64_URL_SAFE_NO_PAD.encode(payload)
+    );
+    let message = signing_input_message(&token);
+    let (signature, _) = crate::sign_with_context(&message, seckey, context);
+    token.push('.');
+    token.push_str(&BASE64_URL_SAFE_NO_PAD.encode(signature.serialize()));
+    token
+}
+
+#[cfg(all(
+    feature = "hmac",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Sign `payload` as a compact JWS with the `ES256K` algorithm. See
+/// `sign_jws_with_context`.
+pub fn sign_jws(payload: &[u8], seckey: &SecretKey) -> String {
+    sign_jws_with_context(payload, seckey, &ECMULT_GEN_CONTEXT)
+}
+
+/// Verify a compact JWS with the `ES256K` algorithm against the public
+/// key, using the given context, and return its payload. Tokens with any
+/// other algorithm, or with critical header extensions, are rejected. Both
+/// low-S and high-S signatures are accepted, as in RFC 8812.
+pub fn verify_jws_with_context(
+    token: &str,
+    pubkey: &PublicKey,
+    context: &ECMultContext,
+) -> Result<Vec<u8>, Error> {
+    let mut parts = token.split('.');
+    let (header, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature)) if parts.next().is_none() => {
+            (header, payload, signature)
+        }
+        _ => return Err(token_error(JoseError::Malformed)),
+    };
+    let signing_input = &token[..(header.len() + 1 + payload.len())];
+    let decode = |part: &str| {
+        BASE64_URL_SAFE_NO_PAD
+            .decode(part)
+            .map_err(|_| token_error(JoseError::Base64))
+    };
+
+    let header: Header =
+        serde_json::from_slice(&decode(header)?).map_err(|_| token_error(JoseError::Json))?;
+    if header.alg != ALG_ES256K {
+        return Err(token_error(JoseError::UnsupportedAlgorithm));
+    }
+    if header.crit.is_some() {
+        return Err(token_error(JoseError::CriticalHeader));
+    }
+    let payload = decode(payload)?;
+    let signature = Signature::parse_standard_slice(&decode(signature)?)?;
+
+    let message = signing_input_message(signing_input);
+    verify_detailed_with_context(
+        &message,
+        &signature,
+        pubkey,
+        VerifyOptions::default(),
+        context,
+    )?;
+
+    Ok(payload)
+}
+
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+/// Verify a compact JWS with the `ES256K` algorithm against the public
+/// key, and return its payload. See `verify_jws_with_context`.
+pub fn verify_jws(token: &str, pubkey: &PublicKey) -> Result<Vec<u8>, Error> {
+    verify_jws_with_context(token, pubkey, &ECMULT_CONTEXT)
+}
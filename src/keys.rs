@@ -0,0 +1,830 @@
+//! DER and PEM encodings of keys: X.509 `SubjectPublicKeyInfo` for public
+//! keys, and SEC1 `ECPrivateKey` and PKCS#8 for secret keys. With the
+//! `pkcs8-encryption` feature, PKCS#8 secret keys can also be encrypted
+//! with PBES2, using PBKDF2-HMAC-SHA256 and AES-CBC.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use arrayref::array_mut_ref;
+#[cfg(feature = "alloc")]
+use base64::{engine::Engine as _, prelude::BASE64_STANDARD};
+#[cfg(feature = "alloc")]
+use zeroize::Zeroize;
+
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+use crate::ECMULT_GEN_CONTEXT;
+use crate::{
+    curve::ECMultGenContext,
+    util::{self, Decoder},
+    DerError, Error, ErrorDetail, ErrorKind, PublicKey, SecretKey,
+};
+
+pub const SPKI_PUBLIC_KEY_SIZE: usize = 88;
+pub const SPKI_COMPRESSED_PUBLIC_KEY_SIZE: usize = 56;
+pub const SEC1_SECRET_KEY_SIZE: usize = 118;
+pub const PKCS8_SECRET_KEY_SIZE: usize = 135;
+
+/// `id-ecPublicKey`, 1.2.840.10045.2.1.
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// `secp256k1`, 1.3.132.0.10.
+const OID_SECP256K1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+/// The `AlgorithmIdentifier` of secp256k1 keys.
+const EC_ALGORITHM: [u8; 18] = [
+    0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b, 0x81, 0x04,
+    0x00, 0x0a,
+];
+
+#[cfg(feature = "alloc")]
+const PEM_PUBLIC_KEY: &str = "PUBLIC KEY";
+#[cfg(feature = "alloc")]
+const PEM_EC_PRIVATE_KEY: &str = "EC PRIVATE KEY";
+#[cfg(feature = "alloc")]
+const PEM_PRIVATE_KEY: &str = "PRIVATE KEY";
+#[cfg(feature = "pkcs8-encryption")]
+const PEM_ENCRYPTED_PRIVATE_KEY: &str = "ENCRYPTED PRIVATE KEY";
+
+/// Give an error from the decoder the kind of the key being parsed.
+fn key_error(kind: ErrorKind, err: Error) -> Error {
+    Error::new(kind).with_detail(err.detail())
+}
+
+/// Read an `AlgorithmIdentifier`, and check that it is `id-ecPublicKey`
+/// with the `secp256k1` named curve. In PKCS#8, the curve is also allowed
+/// to be given only in the inner `ECPrivateKey`, so it is optional there.
+fn read_ec_algorithm(decoder: &mut Decoder, curve_required: bool) -> Result<(), Error> {
+    let mut algorithm = Decoder::new(decoder.read_element(0x30)?);
+    if algorithm.read_element(0x06)? != OID_EC_PUBLIC_KEY {
+        return Err(DerError::UnsupportedAlgorithm.into());
+    }
+    if curve_required || algorithm.remaining_len() > 0 {
+        read_curve(&mut algorithm)?;
+    }
+    algorithm.finish()
+}
+
+fn read_curve(decoder: &mut Decoder) -> Result<(), Error> {
+    match decoder.read_element(0x06) {
+        Ok(OID_SECP256K1) => Ok(()),
+        // Explicit curve parameters are a sequence, not an OID.
+        Ok(_) | Err(_) => Err(DerError::UnsupportedCurve.into()),
+    }
+}
+
+fn parse_spki(p: &[u8]) -> Result<PublicKey, Error> {
+    let mut outer = Decoder::new(p);
+    let mut spki = Decoder::new(outer.read_element(0x30)?);
+    outer.finish()?;
+
+    read_ec_algorithm(&mut spki, true)?;
+    let point = spki.read_bit_string()?;
+    spki.finish()?;
+
+    parse_point(point)
+}
+
+/// Parse the full or compressed point of a `SubjectPublicKeyInfo`, or the
+/// public key given with a secret key.
+fn parse_point(point: &[u8]) -> Result<PublicKey, Error> {
+    match point.len() {
+        util::FULL_PUBLIC_KEY_SIZE | util::COMPRESSED_PUBLIC_KEY_SIZE => {
+            PublicKey::parse_slice(point, None)
+        }
+        _ => Err(Error::new(ErrorKind::InvalidPublicKey)
+            .with_detail(ErrorDetail::UnsupportedLength(point.len()))),
+    }
+}
+
+/// Check that the public key given with `seckey` is its own.
+pub(crate) fn check_public_key(
+    seckey: &SecretKey,
+    pubkey: &PublicKey,
+    context: &ECMultGenContext,
+) -> Result<(), Error> {
+    if PublicKey::from_secret_key_with_context(seckey, context) == *pubkey {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::KeyMismatch))
+    }
+}
+
+/// Parse an `ECPrivateKey`. Its public key, if present, must match the
+/// secret key.
+fn parse_sec1(p: &[u8], context: &ECMultGenContext) -> Result<SecretKey, Error> {
+    let mut outer = Decoder::new(p);
+    let mut sec1 = Decoder::new(outer.read_element(0x30)?);
+    outer.finish()?;
+
+    if sec1.read_u32()? != 1 {
+        return Err(DerError::UnsupportedVersion.into());
+    }
+    let seckey = SecretKey::parse_slice(sec1.read_element(0x04)?)?;
+    if let Some(parameters) = sec1.read_optional_element(0xa0)? {
+        let mut parameters = Decoder::new(parameters);
+        read_curve(&mut parameters)?;
+        parameters.finish()?;
+    }
+    if let Some(pubkey) = sec1.read_optional_element(0xa1)? {
+        let mut pubkey = Decoder::new(pubkey);
+        let point = pubkey.read_bit_string()?;
+        pubkey.finish()?;
+        check_public_key(&seckey, &parse_point(point)?, context)?;
+    }
+    sec1.finish()?;
+
+    Ok(seckey)
+}
+
+fn parse_pkcs8(p: &[u8], context: &ECMultGenContext) -> Result<SecretKey, Error> {
+    let mut outer = Decoder::new(p);
+    let mut pkcs8 = Decoder::new(outer.read_element(0x30)?);
+    outer.finish()?;
+
+    // Version 1 is the `OneAsymmetricKey` of RFC 5958, which may carry
+    // the public key.
+    let version = pkcs8.read_u32()?;
+    if version > 1 {
+        return Err(DerError::UnsupportedVersion.into());
+    }
+    read_ec_algorithm(&mut pkcs8, false)?;
+    let seckey = parse_sec1(pkcs8.read_element(0x04)?, context)?;
+    pkcs8.read_optional_element(0xa0)?;
+    if version == 1 {
+        // The public key is an implicitly tagged bit string.
+        if let Some(point) = pkcs8.read_optional_element(0x81)? {
+            match point.split_first() {
+                Some((0, point)) => check_public_key(&seckey, &parse_point(point)?, context)?,
+                _ => return Err(DerError::InvalidBitString.into()),
+            }
+        }
+    }
+    pkcs8.finish()?;
+
+    Ok(seckey)
+}
+
+/// Encode `ECPrivateKey`, with the curve only if it is not given by an
+/// enclosing PKCS#8 structure.
+fn write_sec1(out: &mut [u8], seckey: &SecretKey, pubkey: &PublicKey, with_curve: bool) {
+    let len = out.len() as u8 - 2;
+    let (header, rest) = out.split_at_mut(7);
+    header.copy_from_slice(&[0x30, len, 0x02, 0x01, 0x01, 0x04, 0x20]);
+    let (secret, rest) = rest.split_at_mut(util::SECRET_KEY_SIZE);
+    seckey
+        .0
+        .fill_b32(array_mut_ref!(secret, 0, util::SECRET_KEY_SIZE));
+    let rest = if with_curve {
+        let (curve, rest) = rest.split_at_mut(9);
+        curve.copy_from_slice(&[0xa0, 0x07, 0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a]);
+        rest
+    } else {
+        rest
+    };
+    rest[..5].copy_from_slice(&[0xa1, 0x44, 0x03, 0x42, 0x00]);
+    rest[5..].copy_from_slice(&pubkey.serialize());
+}
+
+#[cfg(feature = "alloc")]
+/// Encode `der` as PEM with the given label, in lines of 64 characters.
+fn pem_encode(label: &str, der: &[u8]) -> String {
+    let mut body = BASE64_STANDARD.encode(der);
+    let mut pem = String::with_capacity(body.len() + body.len() / 64 + 2 * label.len() + 32);
+    pem.push_str("-----BEGIN ");
+    pem.push_str(label);
+    pem.push_str("-----\n");
+    for line in body.as_bytes().chunks(64) {
+        // The base64 alphabet is ASCII.
+        pem.push_str(core::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str("-----END ");
+    pem.push_str(label);
+    pem.push_str("-----\n");
+    body.zeroize();
+    pem
+}
+
+#[cfg(feature = "alloc")]
+/// Decode PEM, returning its label and contents. Surrounding whitespace and
+/// whitespace inside the body are ignored, but headers are not supported.
+fn pem_decode(pem: &str) -> Result<(&str, Vec<u8>), ErrorDetail> {
+    let pem = pem
+        .trim()
+        .strip_prefix("-----BEGIN ")
+        .ok_or(ErrorDetail::Pem)?;
+    let (label, rest) = pem.split_once("-----").ok_or(ErrorDetail::Pem)?;
+    let (body, end) = rest.split_once("-----END ").ok_or(ErrorDetail::Pem)?;
+    if end.strip_prefix(label) != Some("-----") {
+        return Err(ErrorDetail::Pem);
+    }
+
+    let mut body = body
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect::<Vec<u8>>();
+    let der = BASE64_STANDARD.decode(&body).map_err(|_| ErrorDetail::Pem);
+    body.zeroize();
+    Ok((label, der?))
+}
+
+#[cfg(feature = "alloc")]
+fn pem_decode_labeled(
+    pem: &str,
+    labels: &[&str],
+    kind: ErrorKind,
+) -> Result<(usize, Vec<u8>), Error> {
+    let (label, der) = pem_decode(pem).map_err(|detail| Error::new(kind).with_detail(detail))?;
+    match labels.iter().position(|l| *l == label) {
+        Some(index) => Ok((index, der)),
+        None => Err(Error::new(kind).with_detail(ErrorDetail::Pem)),
+    }
+}
+
+impl PublicKey {
+    /// Parse a DER-encoded X.509 `SubjectPublicKeyInfo`, with the
+    /// `id-ecPublicKey` algorithm and the `secp256k1` named curve, and a
+    /// full or compressed point.
+    pub fn parse_der(p: &[u8]) -> Result<PublicKey, Error> {
+        parse_spki(p).map_err(|e| key_error(ErrorKind::InvalidPublicKey, e))
+    }
+
+    /// Serialize as a DER-encoded X.509 `SubjectPublicKeyInfo`, with the
+    /// full point.
+    pub fn serialize_der(&self) -> [u8; SPKI_PUBLIC_KEY_SIZE] {
+        let mut ret = [0u8; SPKI_PUBLIC_KEY_SIZE];
+        ret[..2].copy_from_slice(&[0x30, 0x56]);
+        ret[2..20].copy_from_slice(&EC_ALGORITHM);
+        ret[20..23].copy_from_slice(&[0x03, 0x42, 0x00]);
+        ret[23..].copy_from_slice(&self.serialize());
+        ret
+    }
+
+    /// Serialize as a DER-encoded X.509 `SubjectPublicKeyInfo`, with the
+    /// compressed point.
+    pub fn serialize_compressed_der(&self) -> [u8; SPKI_COMPRESSED_PUBLIC_KEY_SIZE] {
+        let mut ret = [0u8; SPKI_COMPRESSED_PUBLIC_KEY_SIZE];
+        ret[..2].copy_from_slice(&[0x30, 0x36]);
+        ret[2..20].copy_from_slice(&EC_ALGORITHM);
+        ret[20..23].copy_from_slice(&[0x03, 0x22, 0x00]);
+        ret[23..].copy_from_slice(&self.serialize_compressed());
+        ret
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Parse a `PUBLIC KEY` PEM block. See `parse_der`.
+    pub fn parse_pem(pem: &str) -> Result<PublicKey, Error> {
+        let (_, der) = pem_decode_labeled(pem, &[PEM_PUBLIC_KEY], ErrorKind::InvalidPublicKey)?;
+        Self::parse_der(&der)
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Serialize as a `PUBLIC KEY` PEM block, with the full point.
+    pub fn to_pem(&self) -> String {
+        pem_encode(PEM_PUBLIC_KEY, &self.serialize_der())
+    }
+}
+
+impl SecretKey {
+    /// Parse a DER-encoded SEC1 `ECPrivateKey`. The curve, if given, must
+    /// be `secp256k1`. The public key, if given, must be the one of the
+    /// secret key, which is checked using the given context, or
+    /// `ErrorDetail::KeyMismatch` is returned.
+    pub fn parse_sec1_der_with_context(
+        p: &[u8],
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        parse_sec1(p, context).map_err(|e| key_error(ErrorKind::InvalidSecretKey, e))
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Parse a DER-encoded SEC1 `ECPrivateKey`. See
+    /// `parse_sec1_der_with_context`.
+    pub fn parse_sec1_der(p: &[u8]) -> Result<SecretKey, Error> {
+        Self::parse_sec1_der_with_context(p, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Serialize as a DER-encoded SEC1 `ECPrivateKey`, with the curve and
+    /// the public key computed using the given context.
+    pub fn serialize_sec1_der_with_context(
+        &self,
+        context: &ECMultGenContext,
+    ) -> [u8; SEC1_SECRET_KEY_SIZE] {
+        let pubkey = PublicKey::from_secret_key_with_context(self, context);
+        let mut ret = [0u8; SEC1_SECRET_KEY_SIZE];
+        write_sec1(&mut ret, self, &pubkey, true);
+        ret
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Serialize as a DER-encoded SEC1 `ECPrivateKey`.
+    pub fn serialize_sec1_der(&self) -> [u8; SEC1_SECRET_KEY_SIZE] {
+        self.serialize_sec1_der_with_context(&ECMULT_GEN_CONTEXT)
+    }
+
+    /// Parse a DER-encoded, unencrypted PKCS#8 `PrivateKeyInfo` (or
+    /// `OneAsymmetricKey`) holding an `ECPrivateKey`, using the given
+    /// context to check the public keys of both. See
+    /// `parse_sec1_der_with_context`.
+    pub fn parse_pkcs8_der_with_context(
+        p: &[u8],
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        parse_pkcs8(p, context).map_err(|e| key_error(ErrorKind::InvalidSecretKey, e))
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Parse a DER-encoded, unencrypted PKCS#8 `PrivateKeyInfo` (or
+    /// `OneAsymmetricKey`). See `parse_pkcs8_der_with_context`.
+    pub fn parse_pkcs8_der(p: &[u8]) -> Result<SecretKey, Error> {
+        Self::parse_pkcs8_der_with_context(p, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Serialize as a DER-encoded, unencrypted PKCS#8 `PrivateKeyInfo`,
+    /// with the public key computed using the given context.
+    pub fn serialize_pkcs8_der_with_context(
+        &self,
+        context: &ECMultGenContext,
+    ) -> [u8; PKCS8_SECRET_KEY_SIZE] {
+        let pubkey = PublicKey::from_secret_key_with_context(self, context);
+        let mut ret = [0u8; PKCS8_SECRET_KEY_SIZE];
+        ret[..6].copy_from_slice(&[0x30, 0x81, 0x84, 0x02, 0x01, 0x00]);
+        ret[6..24].copy_from_slice(&EC_ALGORITHM);
+        ret[24..26].copy_from_slice(&[0x04, 0x6d]);
+        write_sec1(&mut ret[26..], self, &pubkey, false);
+        ret
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Serialize as a DER-encoded, unencrypted PKCS#8 `PrivateKeyInfo`.
+    pub fn serialize_pkcs8_der(&self) -> [u8; PKCS8_SECRET_KEY_SIZE] {
+        self.serialize_pkcs8_der_with_context(&ECMULT_GEN_CONTEXT)
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Parse a `PRIVATE KEY` (PKCS#8) or `EC PRIVATE KEY` (SEC1) PEM block,
+    /// using the given context to check the public key.
+    pub fn parse_pem_with_context(
+        pem: &str,
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        let (index, mut der) = pem_decode_labeled(
+            pem,
+            &[PEM_PRIVATE_KEY, PEM_EC_PRIVATE_KEY],
+            ErrorKind::InvalidSecretKey,
+        )?;
+        let seckey = if index == 0 {
+            Self::parse_pkcs8_der_with_context(&der, context)
+        } else {
+            Self::parse_sec1_der_with_context(&der, context)
+        };
+        der.zeroize();
+        seckey
+    }
+
+    #[cfg(all(
+        feature = "alloc",
+        any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        )
+    ))]
+    /// Parse a `PRIVATE KEY` (PKCS#8) or `EC PRIVATE KEY` (SEC1) PEM block.
+    pub fn parse_pem(pem: &str) -> Result<SecretKey, Error> {
+        Self::parse_pem_with_context(pem, &ECMULT_GEN_CONTEXT)
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Serialize as a `PRIVATE KEY` (PKCS#8) PEM block, using the given
+    /// context.
+    pub fn to_pem_with_context(&self, context: &ECMultGenContext) -> String {
+        let mut der = self.serialize_pkcs8_der_with_context(context);
+        let pem = pem_encode(PEM_PRIVATE_KEY, &der);
+        der.zeroize();
+        pem
+    }
+
+    #[cfg(all(
+        feature = "alloc",
+        any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        )
+    ))]
+    /// Serialize as a `PRIVATE KEY` (PKCS#8) PEM block.
+    pub fn to_pem(&self) -> String {
+        self.to_pem_with_context(&ECMULT_GEN_CONTEXT)
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Serialize as an `EC PRIVATE KEY` (SEC1) PEM block, using the given
+    /// context.
+    pub fn to_sec1_pem_with_context(&self, context: &ECMultGenContext) -> String {
+        let mut der = self.serialize_sec1_der_with_context(context);
+        let pem = pem_encode(PEM_EC_PRIVATE_KEY, &der);
+        der.zeroize();
+        pem
+    }
+
+    #[cfg(all(
+        feature = "alloc",
+        any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        )
+    ))]
+    /// Serialize as an `EC PRIVATE KEY` (SEC1) PEM block.
+    pub fn to_sec1_pem(&self) -> String {
+        self.to_sec1_pem_with_context(&ECMULT_GEN_CONTEXT)
+    }
+}
+
+#[cfg(feature = "pkcs8-encryption")]
+pub(crate) mod encryption {
+    use aes::{Aes128, Aes192, Aes256};
+    use alloc::{string::String, vec, vec::Vec};
+    use cbc::cipher::{
+        block_padding::Pkcs7, BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit,
+    };
+    use hmac::Hmac;
+    use rand::Rng;
+    use sha2::Sha256;
+    use zeroize::Zeroize;
+
+    use super::{
+        key_error, pem_decode_labeled, pem_encode, PEM_ENCRYPTED_PRIVATE_KEY, PKCS8_SECRET_KEY_SIZE,
+    };
+    use crate::{
+        curve::ECMultGenContext, util::Decoder, DerError, Error, ErrorDetail, ErrorKind, SecretKey,
+    };
+
+    /// The largest PBKDF2 iteration count of encrypted PKCS#8 keys that is
+    /// read or written, which bounds the time taken to decrypt an untrusted
+    /// key to a few seconds.
+    pub const PBKDF2_MAX_ITERATIONS: u32 = 10_000_000;
+
+    /// `PBES2`, 1.2.840.113549.1.5.13.
+    const OID_PBES2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+    /// `PBKDF2`, 1.2.840.113549.1.5.12.
+    const OID_PBKDF2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+    /// `hmacWithSHA256`, 1.2.840.113549.2.9.
+    const OID_HMAC_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+    /// `aes128-CBC`, 2.16.840.1.101.3.4.1.2.
+    const OID_AES128_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x02];
+    /// `aes192-CBC`, 2.16.840.1.101.3.4.1.22.
+    const OID_AES192_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x16];
+    /// `aes256-CBC`, 2.16.840.1.101.3.4.1.42.
+    const OID_AES256_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+
+    const SALT_SIZE: usize = 16;
+    const IV_SIZE: usize = 16;
+
+    /// The parameters of PBES2 with PBKDF2-HMAC-SHA256 and AES-CBC.
+    struct Pbes2<'a> {
+        salt: &'a [u8],
+        iterations: u32,
+        key_size: usize,
+        iv: &'a [u8],
+    }
+
+    fn read_pbes2<'a>(decoder: &mut Decoder<'a>) -> Result<Pbes2<'a>, Error> {
+        let mut algorithm = Decoder::new(decoder.read_element(0x30)?);
+        if algorithm.read_element(0x06)? != OID_PBES2 {
+            return Err(DerError::UnsupportedAlgorithm.into());
+        }
+        let mut params = Decoder::new(algorithm.read_element(0x30)?);
+        algorithm.finish()?;
+
+        let mut kdf = Decoder::new(params.read_element(0x30)?);
+        if kdf.read_element(0x06)? != OID_PBKDF2 {
+            return Err(DerError::UnsupportedAlgorithm.into());
+        }
+        let mut kdf_params = Decoder::new(kdf.read_element(0x30)?);
+        kdf.finish()?;
+        let salt = kdf_params.read_element(0x04)?;
+        let iterations = kdf_params.read_u32()?;
+        if iterations == 0 {
+            return Err(DerError::InvalidInteger.into());
+        }
+        if iterations > PBKDF2_MAX_ITERATIONS {
+            return Err(DerError::TooManyIterations.into());
+        }
+        let key_size = match kdf_params.peek(0) {
+            Ok(0x02) => Some(kdf_params.read_u32()? as usize),
+            _ => None,
+        };
+        // The default PRF is HMAC-SHA1, which is not supported.
+        let mut prf = Decoder::new(
+            kdf_params
+                .read_element(0x30)
+                .map_err(|_| Error::from(DerError::UnsupportedAlgorithm))?,
+        );
+        if prf.read_element(0x06)? != OID_HMAC_SHA256 {
+            return Err(DerError::UnsupportedAlgorithm.into());
+        }
+        if let Some(null) = prf.read_optional_element(0x05)? {
+            if !null.is_empty() {
+                return Err(DerError::InvalidLength.into());
+            }
+        }
+        prf.finish()?;
+        kdf_params.finish()?;
+
+        let mut cipher = Decoder::new(params.read_element(0x30)?);
+        let cipher_key_size = match cipher.read_element(0x06)? {
+            OID_AES128_CBC => 16,
+            OID_AES192_CBC => 24,
+            OID_AES256_CBC => 32,
+            _ => return Err(DerError::UnsupportedAlgorithm.into()),
+        };
+        let iv = cipher.read_element(0x04)?;
+        if iv.len() != IV_SIZE {
+            return Err(DerError::InvalidLength.into());
+        }
+        cipher.finish()?;
+        params.finish()?;
+
+        if key_size.is_some_and(|size| size != cipher_key_size) {
+            return Err(DerError::UnsupportedAlgorithm.into());
+        }
+
+        Ok(Pbes2 {
+            salt,
+            iterations,
+            key_size: cipher_key_size,
+            iv,
+        })
+    }
+
+    fn decrypt_cbc<C>(key: &[u8], iv: &[u8], data: &mut [u8]) -> Option<usize>
+    where
+        C: BlockCipher + BlockDecryptMut + KeyInit,
+    {
+        let decryptor = cbc::Decryptor::<C>::new_from_slices(key, iv).ok()?;
+        decryptor
+            .decrypt_padded_mut::<Pkcs7>(data)
+            .ok()
+            .map(|plaintext| plaintext.len())
+    }
+
+    fn parse_encrypted_pkcs8(
+        p: &[u8],
+        password: &[u8],
+        context: &ECMultGenContext,
+    ) -> Result<SecretKey, Error> {
+        let mut outer = Decoder::new(p);
+        let mut info = Decoder::new(outer.read_element(0x30)?);
+        outer.finish()?;
+
+        let pbes2 = read_pbes2(&mut info)?;
+        let mut data = info.read_element(0x04)?.to_vec();
+        info.finish()?;
+
+        let mut key = [0u8; 32];
+        let key = &mut key[..pbes2.key_size];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(password, pbes2.salt, pbes2.iterations, key);
+        let len = match pbes2.key_size {
+            16 => decrypt_cbc::<Aes128>(key, pbes2.iv, &mut data),
+            24 => decrypt_cbc::<Aes192>(key, pbes2.iv, &mut data),
+            _ => decrypt_cbc::<Aes256>(key, pbes2.iv, &mut data),
+        };
+        key.zeroize();
+
+        let seckey = match len {
+            Some(len) => super::parse_pkcs8(&data[..len], context),
+            None => {
+                Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::Decryption))
+            }
+        };
+        data.zeroize();
+        seckey
+    }
+
+    /// Append a DER element with the given tag and contents.
+    fn push_element(out: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+        out.push(tag);
+        let len = contents.len();
+        if len < 0x80 {
+            out.push(len as u8);
+        } else if len <= 0xff {
+            out.extend_from_slice(&[0x81, len as u8]);
+        } else {
+            out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]);
+        }
+        out.extend_from_slice(contents);
+    }
+
+    fn push_u32(out: &mut Vec<u8>, value: u32) {
+        let bytes = value.to_be_bytes();
+        let skip = bytes.iter().take(3).take_while(|b| **b == 0).count();
+        if bytes[skip] & 0x80 == 0 {
+            push_element(out, 0x02, &bytes[skip..]);
+        } else {
+            let mut padded = vec![0u8];
+            padded.extend_from_slice(&bytes[skip..]);
+            push_element(out, 0x02, &padded);
+        }
+    }
+
+    impl SecretKey {
+        /// Parse a DER-encoded PKCS#8 `EncryptedPrivateKeyInfo`, encrypted
+        /// with PBES2 using PBKDF2-HMAC-SHA256 and AES-128, AES-192 or
+        /// AES-256 in CBC mode. Keys with more than
+        /// `util::PBKDF2_MAX_ITERATIONS` iterations are rejected before
+        /// decrypting, with `DerError::TooManyIterations`. The public key is
+        /// checked using the given context, as in
+        /// `parse_pkcs8_der_with_context`.
+        pub fn parse_encrypted_pkcs8_der_with_context(
+            p: &[u8],
+            password: &[u8],
+            context: &ECMultGenContext,
+        ) -> Result<SecretKey, Error> {
+            parse_encrypted_pkcs8(p, password, context)
+                .map_err(|e| key_error(ErrorKind::InvalidSecretKey, e))
+        }
+
+        #[cfg(any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        ))]
+        /// Parse a DER-encoded PKCS#8 `EncryptedPrivateKeyInfo`. See
+        /// `parse_encrypted_pkcs8_der_with_context`.
+        pub fn parse_encrypted_pkcs8_der(p: &[u8], password: &[u8]) -> Result<SecretKey, Error> {
+            Self::parse_encrypted_pkcs8_der_with_context(p, password, &crate::ECMULT_GEN_CONTEXT)
+        }
+
+        /// Serialize as a DER-encoded PKCS#8 `EncryptedPrivateKeyInfo`,
+        /// encrypted with PBES2 using PBKDF2-HMAC-SHA256 with the given
+        /// number of iterations and AES-256-CBC, with a random salt and IV.
+        /// Panics if `iterations` is zero or above
+        /// `util::PBKDF2_MAX_ITERATIONS`.
+        pub fn serialize_encrypted_pkcs8_der_with_context<R: Rng>(
+            &self,
+            password: &[u8],
+            iterations: u32,
+            rng: &mut R,
+            context: &ECMultGenContext,
+        ) -> Vec<u8> {
+            assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+            assert!(
+                iterations <= PBKDF2_MAX_ITERATIONS,
+                "PBKDF2 iteration count above the supported maximum"
+            );
+
+            let mut salt = [0u8; SALT_SIZE];
+            let mut iv = [0u8; IV_SIZE];
+            rng.fill_bytes(&mut salt);
+            rng.fill_bytes(&mut iv);
+
+            let mut key = [0u8; 32];
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(password, &salt, iterations, &mut key);
+            let mut data = [0u8; PKCS8_SECRET_KEY_SIZE + 16];
+            let mut plaintext = self.serialize_pkcs8_der_with_context(context);
+            data[..PKCS8_SECRET_KEY_SIZE].copy_from_slice(&plaintext);
+            let encryptor = cbc::Encryptor::<Aes256>::new(&key.into(), &iv.into());
+            let len = encryptor
+                .encrypt_padded_mut::<Pkcs7>(&mut data, PKCS8_SECRET_KEY_SIZE)
+                .expect("the buffer has room for a full padding block")
+                .len();
+            key.zeroize();
+            plaintext.zeroize();
+
+            let mut kdf_params = Vec::new();
+            push_element(&mut kdf_params, 0x04, &salt);
+            push_u32(&mut kdf_params, iterations);
+            let mut prf = Vec::new();
+            push_element(&mut prf, 0x06, OID_HMAC_SHA256);
+            push_element(&mut prf, 0x05, &[]);
+            push_element(&mut kdf_params, 0x30, &prf);
+
+            let mut kdf = Vec::new();
+            push_element(&mut kdf, 0x06, OID_PBKDF2);
+            push_element(&mut kdf, 0x30, &kdf_params);
+
+            let mut cipher = Vec::new();
+            push_element(&mut cipher, 0x06, OID_AES256_CBC);
+            push_element(&mut cipher, 0x04, &iv);
+
+            let mut params = Vec::new();
+            push_element(&mut params, 0x30, &kdf);
+            push_element(&mut params, 0x30, &cipher);
+
+            let mut algorithm = Vec::new();
+            push_element(&mut algorithm, 0x06, OID_PBES2);
+            push_element(&mut algorithm, 0x30, &params);
+
+            let mut info = Vec::new();
+            push_element(&mut info, 0x30, &algorithm);
+            push_element(&mut info, 0x04, &data[..len]);
+
+            let mut ret = Vec::new();
+            push_element(&mut ret, 0x30, &info);
+            ret
+        }
+
+        #[cfg(any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        ))]
+        /// Serialize as a DER-encoded PKCS#8 `EncryptedPrivateKeyInfo`. See
+        /// `serialize_encrypted_pkcs8_der_with_context`.
+        pub fn serialize_encrypted_pkcs8_der<R: Rng>(
+            &self,
+            password: &[u8],
+            iterations: u32,
+            rng: &mut R,
+        ) -> Vec<u8> {
+            self.serialize_encrypted_pkcs8_der_with_context(
+                password,
+                iterations,
+                rng,
+                &crate::ECMULT_GEN_CONTEXT,
+            )
+        }
+
+        /// Parse an `ENCRYPTED PRIVATE KEY` PEM block, using the given
+        /// context. See `parse_encrypted_pkcs8_der_with_context`.
+        pub fn parse_encrypted_pem_with_context(
+            pem: &str,
+            password: &[u8],
+            context: &ECMultGenContext,
+        ) -> Result<SecretKey, Error> {
+            let (_, der) = pem_decode_labeled(
+                pem,
+                &[PEM_ENCRYPTED_PRIVATE_KEY],
+                ErrorKind::InvalidSecretKey,
+            )?;
+            Self::parse_encrypted_pkcs8_der_with_context(&der, password, context)
+        }
+
+        #[cfg(any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        ))]
+        /// Parse an `ENCRYPTED PRIVATE KEY` PEM block. See
+        /// `parse_encrypted_pkcs8_der_with_context`.
+        pub fn parse_encrypted_pem(pem: &str, password: &[u8]) -> Result<SecretKey, Error> {
+            Self::parse_encrypted_pem_with_context(pem, password, &crate::ECMULT_GEN_CONTEXT)
+        }
+
+        /// Serialize as an `ENCRYPTED PRIVATE KEY` PEM block, using the
+        /// given context. See `serialize_encrypted_pkcs8_der_with_context`.
+        pub fn to_encrypted_pem_with_context<R: Rng>(
+            &self,
+            password: &[u8],
+            iterations: u32,
+            rng: &mut R,
+            context: &ECMultGenContext,
+        ) -> String {
+            let der =
+                self.serialize_encrypted_pkcs8_der_with_context(password, iterations, rng, context);
+            pem_encode(PEM_ENCRYPTED_PRIVATE_KEY, &der)
+        }
+
+        #[cfg(any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        ))]
+        /// Serialize as an `ENCRYPTED PRIVATE KEY` PEM block. See
+        /// `serialize_encrypted_pkcs8_der_with_context`.
+        pub fn to_encrypted_pem<R: Rng>(
+            &self,
+            password: &[u8],
+            iterations: u32,
+            rng: &mut R,
+        ) -> String {
+            self.to_encrypted_pem_with_context(
+                password,
+                iterations,
+                rng,
+                &crate::ECMULT_GEN_CONTEXT,
+            )
+        }
+    }
+}
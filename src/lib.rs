@@ -1,6 +1,12 @@
 //! Pure Rust implementation of the secp256k1 curve and fast ECDSA
 //! signatures. The secp256k1 curve is used extensively in Bitcoin and
 //! Ethereum-alike cryptocurrencies.
+//!
+//! Both context types are `Send` and `Sync`, so the static `ECMULT_CONTEXT`
+//! and `ECMULT_GEN_CONTEXT` can be used from any number of threads at once,
+//! and there is no need to build a context per thread. With the `rayon`
+//! feature, `par_verify`, `par_recover` and `par_sign_batch` process slices
+//! on the rayon thread pool.
 
 #![deny(
     unused_import_braces,
@@ -16,12 +22,54 @@
 
 pub use libsecp256k1_core::*;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(all(feature = "const-context", not(feature = "lazy-static-context")))]
+mod const_tables;
+#[cfg(feature = "cose")]
+mod cose;
+#[cfg(feature = "jose")]
+mod jose;
+mod keys;
+#[cfg(all(feature = "secure-memory", target_os = "linux"))]
+mod locked;
+#[cfg(feature = "cose")]
+pub use crate::cose::*;
+#[cfg(feature = "jose")]
+pub use crate::jose::*;
+#[cfg(all(feature = "secure-memory", target_os = "linux"))]
+pub use crate::locked::LockedBox;
+
+/// Utilities to manipulate the secp256k1 curve parameters, and the sizes
+/// of the key encodings.
+pub mod util {
+    #[cfg(feature = "cose")]
+    pub use crate::cose::{COSE_PUBLIC_KEY_SIZE, COSE_SECRET_KEY_SIZE};
+    pub use libsecp256k1_core::util::*;
+
+    #[cfg(feature = "pkcs8-encryption")]
+    pub use crate::keys::encryption::PBKDF2_MAX_ITERATIONS;
+    pub use crate::keys::{
+        PKCS8_SECRET_KEY_SIZE, SEC1_SECRET_KEY_SIZE, SPKI_COMPRESSED_PUBLIC_KEY_SIZE,
+        SPKI_PUBLIC_KEY_SIZE,
+    };
+}
+
 use arrayref::{array_mut_ref, array_ref};
-use base64::{engine::Engine as _, prelude::BASE64_STANDARD};
 use core::convert::TryFrom;
 use digest::{generic_array::GenericArray, Digest};
 use rand::Rng;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use base64::{engine::Engine as _, prelude::BASE64_STANDARD};
 #[cfg(feature = "std")]
 use core::fmt;
 #[cfg(feature = "hmac")]
@@ -33,6 +81,11 @@ use sha2::Sha256;
 #[cfg(feature = "hmac")]
 use typenum::U32;
 
+#[cfg(feature = "alloc")]
+use crate::{
+    curve::AffineStorage,
+    util::{inv_all_var_to, odd_multiples_table_storage_var, set_all_gej_var},
+};
 use crate::{
     curve::{Affine, ECMultContext, ECMultGenContext, Field, Jacobian, Scalar},
     util::{Decoder, SignatureArray},
@@ -47,13 +100,31 @@ lazy_static::lazy_static! {
     pub static ref ECMULT_GEN_CONTEXT: Box<ECMultGenContext> = ECMultGenContext::new_boxed();
 }
 
-#[cfg(all(feature = "static-context", not(feature = "lazy-static-context")))]
+#[cfg(all(feature = "const-context", not(feature = "lazy-static-context")))]
+/// A static ECMult context.
+// Evaluated by the compiler, which takes minutes for the default `WINDOW_G`.
+pub static ECMULT_CONTEXT: ECMultContext = const_tables::ECMULT_CONTEXT;
+
+#[cfg(all(feature = "const-context", not(feature = "lazy-static-context")))]
+/// A static ECMultGen context.
+// Evaluated by the compiler.
+pub static ECMULT_GEN_CONTEXT: ECMultGenContext = const_tables::ECMULT_GEN_CONTEXT;
+
+#[cfg(all(
+    feature = "static-context",
+    not(feature = "lazy-static-context"),
+    not(feature = "const-context")
+))]
 /// A static ECMult context.
 // Correct `pre_g` values are fed into `ECMultContext::new_from_raw`, generated by build script.
 pub static ECMULT_CONTEXT: ECMultContext =
     unsafe { ECMultContext::new_from_raw(include!(concat!(env!("OUT_DIR"), "/const.rs"))) };
 
-#[cfg(all(feature = "static-context", not(feature = "lazy-static-context")))]
+#[cfg(all(
+    feature = "static-context",
+    not(feature = "lazy-static-context"),
+    not(feature = "const-context")
+))]
 /// A static ECMultGen context.
 // Correct `prec` values are fed into `ECMultGenContext::new_from_raw`, generated by build script.
 pub static ECMULT_GEN_CONTEXT: ECMultGenContext =
@@ -63,8 +134,11 @@ pub static ECMULT_GEN_CONTEXT: ECMultGenContext =
 /// Public key on a secp256k1 curve.
 pub struct PublicKey(Affine);
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-/// Secret key (256-bit) on a secp256k1 curve.
+#[derive(Clone, Copy)]
+/// Secret key (256-bit) on a secp256k1 curve. Its `Debug` output does not
+/// contain the key, and comparing two keys is constant time. Being `Copy`,
+/// it is duplicated freely and never cleared automatically; prefer
+/// `ZeroizingSecretKey` to keep keys around.
 pub struct SecretKey(Scalar);
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -82,17 +156,12 @@ pub struct RecoveryId(u8);
 /// Hashed message input to an ECDSA signature.
 pub struct Message(pub Scalar);
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-/// Shared secret using ECDH.
+#[derive(Clone)]
+/// Shared secret using ECDH. It is zeroized when dropped, its `Debug`
+/// output does not contain the secret, and it is compared in constant
+/// time.
 pub struct SharedSecret<D: Digest>(GenericArray<u8, D::OutputSize>);
 
-impl<D> Copy for SharedSecret<D>
-where
-    D: Copy + Digest,
-    GenericArray<u8, D::OutputSize>: Copy,
-{
-}
-
 /// Format for public key parsing.
 pub enum PublicKeyFormat {
     /// Compressed public key, 33 bytes.
@@ -115,11 +184,46 @@ impl PublicKey {
         PublicKey(p)
     }
 
-    #[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
     pub fn from_secret_key(seckey: &SecretKey) -> PublicKey {
         Self::from_secret_key_with_context(seckey, &ECMULT_GEN_CONTEXT)
     }
 
+    /// Compute the public keys of a batch of secret keys, using the given
+    /// context. All keys are brought to affine coordinates with a single
+    /// shared field inversion, which makes this much faster than calling
+    /// `from_secret_key_with_context` for each key. Unlike the latter, the
+    /// conversion to affine coordinates is not constant time.
+    #[cfg(feature = "alloc")]
+    pub fn from_secret_keys_batch_with_context(
+        seckeys: &[SecretKey],
+        context: &ECMultGenContext,
+    ) -> Vec<PublicKey> {
+        let mut pjs = vec![Jacobian::default(); seckeys.len()];
+        for (pj, seckey) in pjs.iter_mut().zip(seckeys) {
+            context.ecmult_gen(pj, &seckey.0);
+        }
+        set_all_gej_var(&pjs).into_iter().map(PublicKey).collect()
+    }
+
+    #[cfg(all(
+        feature = "alloc",
+        any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        )
+    ))]
+    /// Compute the public keys of a batch of secret keys. See
+    /// `from_secret_keys_batch_with_context`.
+    pub fn from_secret_keys_batch(seckeys: &[SecretKey]) -> Vec<PublicKey> {
+        Self::from_secret_keys_batch_with_context(seckeys, &ECMULT_GEN_CONTEXT)
+    }
+
     pub fn parse_slice(p: &[u8], format: Option<PublicKeyFormat>) -> Result<PublicKey, Error> {
         let format = match (p.len(), format) {
             (util::FULL_PUBLIC_KEY_SIZE, None)
@@ -130,7 +234,19 @@ impl PublicKey {
             }
             (util::RAW_PUBLIC_KEY_SIZE, None)
             | (util::RAW_PUBLIC_KEY_SIZE, Some(PublicKeyFormat::Raw)) => PublicKeyFormat::Raw,
-            _ => return Err(Error::InvalidInputLength),
+            (actual, Some(format)) => {
+                let expected = match format {
+                    PublicKeyFormat::Full => util::FULL_PUBLIC_KEY_SIZE,
+                    PublicKeyFormat::Compressed => util::COMPRESSED_PUBLIC_KEY_SIZE,
+                    PublicKeyFormat::Raw => util::RAW_PUBLIC_KEY_SIZE,
+                };
+                return Err(Error::length(Input::PublicKey, expected, actual));
+            }
+            (actual, None) => {
+                return Err(Error::new(ErrorKind::InvalidInputLength)
+                    .with_input(Input::PublicKey)
+                    .with_detail(ErrorDetail::UnsupportedLength(actual)))
+            }
         };
 
         match format {
@@ -162,30 +278,38 @@ impl PublicKey {
             || p[0] == TAG_PUBKEY_HYBRID_EVEN
             || p[0] == TAG_PUBKEY_HYBRID_ODD)
         {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::InvalidTag(p[0]))
+            );
         }
         let mut x = Field::default();
         let mut y = Field::default();
         if !x.set_b32(array_ref!(p, 1, 32)) {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::FieldOverflow)
+            );
         }
         if !y.set_b32(array_ref!(p, 33, 32)) {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::FieldOverflow)
+            );
         }
         let mut elem = Affine::default();
         elem.set_xy(&x, &y);
         if (p[0] == TAG_PUBKEY_HYBRID_EVEN || p[0] == TAG_PUBKEY_HYBRID_ODD)
             && (y.is_odd() != (p[0] == TAG_PUBKEY_HYBRID_ODD))
         {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::ParityMismatch)
+            );
         }
         if elem.is_infinity() {
-            return Err(Error::InvalidPublicKey);
+            return Err(Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::Infinity));
         }
         if elem.is_valid_var() {
             Ok(PublicKey(elem))
         } else {
-            Err(Error::InvalidPublicKey)
+            Err(Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::NotOnCurve))
         }
     }
 
@@ -195,21 +319,29 @@ impl PublicKey {
         use util::{TAG_PUBKEY_EVEN, TAG_PUBKEY_ODD};
 
         if !(p[0] == TAG_PUBKEY_EVEN || p[0] == TAG_PUBKEY_ODD) {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::InvalidTag(p[0]))
+            );
         }
         let mut x = Field::default();
         if !x.set_b32(array_ref!(p, 1, 32)) {
-            return Err(Error::InvalidPublicKey);
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::FieldOverflow)
+            );
         }
         let mut elem = Affine::default();
-        elem.set_xo_var(&x, p[0] == TAG_PUBKEY_ODD);
+        if !elem.set_xo_var(&x, p[0] == TAG_PUBKEY_ODD) {
+            return Err(
+                Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::NotOnCurve)
+            );
+        }
         if elem.is_infinity() {
-            return Err(Error::InvalidPublicKey);
+            return Err(Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::Infinity));
         }
         if elem.is_valid_var() {
             Ok(PublicKey(elem))
         } else {
-            Err(Error::InvalidPublicKey)
+            Err(Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::NotOnCurve))
         }
     }
 
@@ -261,14 +393,18 @@ impl PublicKey {
         context.ecmult(&mut r, &a, &one, &tweak.0);
 
         if r.is_infinity() {
-            return Err(Error::TweakOutOfRange);
+            return Err(Error::new(ErrorKind::TweakOutOfRange).with_detail(ErrorDetail::Infinity));
         }
 
         self.0.set_gej(&r);
         Ok(())
     }
 
-    #[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
     pub fn tweak_add_assign(&mut self, tweak: &SecretKey) -> Result<(), Error> {
         self.tweak_add_assign_with_context(tweak, &ECMULT_CONTEXT)
     }
@@ -279,7 +415,7 @@ impl PublicKey {
         context: &ECMultContext,
     ) -> Result<(), Error> {
         if tweak.0.is_zero() {
-            return Err(Error::TweakOutOfRange);
+            return Err(Error::new(ErrorKind::TweakOutOfRange).with_detail(ErrorDetail::ZeroScalar));
         }
 
         let mut r = Jacobian::default();
@@ -291,7 +427,11 @@ impl PublicKey {
         Ok(())
     }
 
-    #[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
     pub fn tweak_mul_assign(&mut self, tweak: &SecretKey) -> Result<(), Error> {
         self.tweak_mul_assign_with_context(tweak, &ECMULT_CONTEXT)
     }
@@ -305,7 +445,7 @@ impl PublicKey {
         }
 
         if qj.is_infinity() {
-            return Err(Error::InvalidPublicKey);
+            return Err(Error::new(ErrorKind::InvalidPublicKey).with_detail(ErrorDetail::Infinity));
         }
 
         let q = Affine::from_gej(&qj);
@@ -313,6 +453,28 @@ impl PublicKey {
     }
 }
 
+impl ConstantTimeEq for PublicKey {
+    fn ct_eq(&self, other: &PublicKey) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for PublicKey {
+    fn conditional_select(a: &PublicKey, b: &PublicKey, choice: Choice) -> PublicKey {
+        PublicKey(Affine::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl core::ops::Neg for &PublicKey {
+    type Output = PublicKey;
+
+    fn neg(self) -> PublicKey {
+        let mut elem = self.0.neg();
+        elem.y.normalize();
+        PublicKey(elem)
+    }
+}
+
 impl Into<Affine> for PublicKey {
     fn into(self) -> Affine {
         self.0
@@ -323,8 +485,10 @@ impl TryFrom<Affine> for PublicKey {
     type Error = Error;
 
     fn try_from(value: Affine) -> Result<Self, Self::Error> {
-        if value.is_infinity() || !value.is_valid_var() {
-            Err(Error::InvalidAffine)
+        if value.is_infinity() {
+            Err(Error::new(ErrorKind::InvalidAffine).with_detail(ErrorDetail::Infinity))
+        } else if !value.is_valid_var() {
+            Err(Error::new(ErrorKind::InvalidAffine).with_detail(ErrorDetail::NotOnCurve))
         } else {
             Ok(PublicKey(value))
         }
@@ -366,10 +530,15 @@ impl<'de> de::Visitor<'de> for PublicKeyStrVisitor {
             33 => PublicKeyFormat::Compressed,
             64 => PublicKeyFormat::Raw,
             65 => PublicKeyFormat::Full,
-            _ => return Err(E::custom(Error::InvalidInputLength)),
+            len => {
+                return Err(E::custom(
+                    Error::new(ErrorKind::InvalidInputLength)
+                        .with_input(Input::PublicKey)
+                        .with_detail(ErrorDetail::UnsupportedLength(len)),
+                ))
+            }
         };
-        PublicKey::parse_slice(value, Some(key_format))
-            .map_err(|_e| E::custom(Error::InvalidPublicKey))
+        PublicKey::parse_slice(value, Some(key_format)).map_err(E::custom)
     }
 }
 
@@ -390,7 +559,7 @@ impl<'de> de::Visitor<'de> for PublicKeyBytesVisitor {
     where
         E: de::Error,
     {
-        PublicKey::parse_slice(value, None).map_err(|_e| E::custom(Error::InvalidPublicKey))
+        PublicKey::parse_slice(value, None).map_err(E::custom)
     }
 }
 
@@ -414,26 +583,34 @@ impl SecretKey {
         if !bool::from(elem.set_b32(p)) {
             Self::try_from(elem)
         } else {
-            Err(Error::InvalidSecretKey)
+            Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::ScalarOverflow))
         }
     }
 
     pub fn parse_slice(p: &[u8]) -> Result<SecretKey, Error> {
         if p.len() != util::SECRET_KEY_SIZE {
-            return Err(Error::InvalidInputLength);
+            return Err(Error::length(
+                Input::SecretKey,
+                util::SECRET_KEY_SIZE,
+                p.len(),
+            ));
         }
 
         let mut a = [0; 32];
         a.copy_from_slice(p);
-        Self::parse(&a)
+        let key = Self::parse(&a);
+        a.zeroize();
+        key
     }
 
     pub fn random<R: Rng>(rng: &mut R) -> SecretKey {
         loop {
             let mut ret = [0u8; util::SECRET_KEY_SIZE];
             rng.fill_bytes(&mut ret);
+            let key = Self::parse(&ret);
+            ret.zeroize();
 
-            if let Ok(key) = Self::parse(&ret) {
+            if let Ok(key) = key {
                 return key;
             }
         }
@@ -446,7 +623,7 @@ impl SecretKey {
     pub fn tweak_add_assign(&mut self, tweak: &SecretKey) -> Result<(), Error> {
         let v = self.0 + tweak.0;
         if v.is_zero() {
-            return Err(Error::TweakOutOfRange);
+            return Err(Error::new(ErrorKind::TweakOutOfRange).with_detail(ErrorDetail::ZeroScalar));
         }
         self.0 = v;
         Ok(())
@@ -454,7 +631,7 @@ impl SecretKey {
 
     pub fn tweak_mul_assign(&mut self, tweak: &SecretKey) -> Result<(), Error> {
         if tweak.0.is_zero() {
-            return Err(Error::TweakOutOfRange);
+            return Err(Error::new(ErrorKind::TweakOutOfRange).with_detail(ErrorDetail::ZeroScalar));
         }
 
         self.0 *= &tweak.0;
@@ -499,7 +676,7 @@ impl TryFrom<Scalar> for SecretKey {
 
     fn try_from(scalar: Scalar) -> Result<Self, Error> {
         if scalar.is_zero() {
-            Err(Error::InvalidSecretKey)
+            Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::ZeroScalar))
         } else {
             Ok(Self(scalar))
         }
@@ -514,6 +691,107 @@ impl core::fmt::LowerHex for SecretKey {
     }
 }
 
+impl core::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &SecretKey) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &SecretKey) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for SecretKey {
+    fn conditional_select(a: &SecretKey, b: &SecretKey, choice: Choice) -> SecretKey {
+        SecretKey(Scalar::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl core::ops::Neg for &SecretKey {
+    type Output = SecretKey;
+
+    fn neg(self) -> SecretKey {
+        SecretKey(-self.0)
+    }
+}
+
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+/// Secret key that is not `Copy`, is zeroized when dropped, and whose
+/// `Debug` output does not contain the key. It dereferences to
+/// `SecretKey`, so it can be used with every function taking a
+/// `&SecretKey`. Existing `SecretKey` values are converted with `From`.
+pub struct ZeroizingSecretKey(SecretKey);
+
+impl ZeroizingSecretKey {
+    pub fn parse(p: &[u8; util::SECRET_KEY_SIZE]) -> Result<ZeroizingSecretKey, Error> {
+        SecretKey::parse(p).map(Self)
+    }
+
+    pub fn parse_slice(p: &[u8]) -> Result<ZeroizingSecretKey, Error> {
+        SecretKey::parse_slice(p).map(Self)
+    }
+
+    pub fn random<R: Rng>(rng: &mut R) -> ZeroizingSecretKey {
+        Self(SecretKey::random(rng))
+    }
+}
+
+impl From<SecretKey> for ZeroizingSecretKey {
+    fn from(seckey: SecretKey) -> Self {
+        Self(seckey)
+    }
+}
+
+impl core::ops::Deref for ZeroizingSecretKey {
+    type Target = SecretKey;
+
+    fn deref(&self) -> &SecretKey {
+        &self.0
+    }
+}
+
+impl core::ops::DerefMut for ZeroizingSecretKey {
+    fn deref_mut(&mut self) -> &mut SecretKey {
+        &mut self.0
+    }
+}
+
+impl core::fmt::Debug for ZeroizingSecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ZeroizingSecretKey(..)")
+    }
+}
+
+impl Zeroize for ZeroizingSecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for ZeroizingSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for ZeroizingSecretKey {}
+
 impl Signature {
     /// Parse an possibly overflowing signature.
     ///
@@ -547,7 +825,9 @@ impl Signature {
         let overflowed_s = s.set_b32(array_ref!(p, 32, 32));
 
         if bool::from(overflowed_r | overflowed_s) {
-            return Err(Error::InvalidSignature);
+            return Err(
+                Error::new(ErrorKind::InvalidSignature).with_detail(ErrorDetail::ScalarOverflow)
+            );
         }
 
         Ok(Signature { r, s })
@@ -560,7 +840,11 @@ impl Signature {
     /// `parse_standard_slice` instead.
     pub fn parse_overflowing_slice(p: &[u8]) -> Result<Signature, Error> {
         if p.len() != util::SIGNATURE_SIZE {
-            return Err(Error::InvalidInputLength);
+            return Err(Error::length(
+                Input::Signature,
+                util::SIGNATURE_SIZE,
+                p.len(),
+            ));
         }
 
         let mut a = [0; util::SIGNATURE_SIZE];
@@ -571,7 +855,11 @@ impl Signature {
     /// Parse a standard signature slice. See also `parse_standard`.
     pub fn parse_standard_slice(p: &[u8]) -> Result<Signature, Error> {
         if p.len() != util::SIGNATURE_SIZE {
-            return Err(Error::InvalidInputLength);
+            return Err(Error::length(
+                Input::Signature,
+                util::SIGNATURE_SIZE,
+                p.len(),
+            ));
         }
 
         let mut a = [0; util::SIGNATURE_SIZE];
@@ -579,7 +867,10 @@ impl Signature {
         Ok(Self::parse_standard(&a)?)
     }
 
-    /// Parse a DER-encoded byte slice to a signature.
+    /// Parse a DER-encoded byte slice to a signature. This accepts the same
+    /// encodings as the C libsecp256k1, which are slightly more permissive
+    /// than BIP-66, such as long-form lengths; use `parse_der_strict` to
+    /// enforce BIP-66 exactly.
     pub fn parse_der(p: &[u8]) -> Result<Signature, Error> {
         let mut decoder = Decoder::new(p);
 
@@ -587,19 +878,51 @@ impl Signature {
         let rlen = decoder.read_len()?;
 
         if rlen != decoder.remaining_len() {
-            return Err(Error::InvalidSignature);
+            return Err(DerError::InvalidSequenceLength.into());
         }
 
         let r = decoder.read_integer()?;
         let s = decoder.read_integer()?;
 
         if decoder.remaining_len() != 0 {
-            return Err(Error::InvalidSignature);
+            return Err(DerError::InvalidSLength.into());
         }
 
         Ok(Signature { r, s })
     }
 
+    /// Parse a DER-encoded byte slice to a signature, enforcing exactly the
+    /// encoding rules of BIP-66, without a trailing sighash byte. On
+    /// failure, the error tells which rule is violated. As with `parse_der`,
+    /// an R or S that is not below the group order is parsed as zero.
+    pub fn parse_der_strict(p: &[u8]) -> Result<Signature, DerError> {
+        let (r, s) = util::parse_der_strict(p)?;
+        Ok(Signature { r, s })
+    }
+
+    /// Parse a BIP-66 signature followed by a sighash byte, as found in
+    /// Bitcoin scripts, returning the signature and the sighash byte. The
+    /// sighash byte itself is not checked.
+    pub fn parse_der_strict_with_sighash(p: &[u8]) -> Result<(Signature, u8), DerError> {
+        let (sighash, der) = p.split_last().ok_or(DerError::MissingSighash)?;
+        Ok((Self::parse_der_strict(der)?, *sighash))
+    }
+
+    /// Check that a byte slice follows exactly the encoding rules of BIP-66,
+    /// without a trailing sighash byte, without parsing R and S. This
+    /// accepts the same inputs as `parse_der_strict`.
+    pub fn check_der(p: &[u8]) -> Result<(), DerError> {
+        util::check_der_strict(p)
+    }
+
+    /// Check a BIP-66 signature followed by a sighash byte without parsing
+    /// R and S, returning the sighash byte.
+    pub fn check_der_with_sighash(p: &[u8]) -> Result<u8, DerError> {
+        let (sighash, der) = p.split_last().ok_or(DerError::MissingSighash)?;
+        util::check_der_strict(der)?;
+        Ok(*sighash)
+    }
+
     /// Converts a "lax DER"-encoded byte slice to a signature. This is basically
     /// only useful for validating signatures in the Bitcoin blockchain from before
     /// 2016. It should never be used in new applications. This library does not
@@ -700,7 +1023,7 @@ impl Message {
 
     pub fn parse_slice(p: &[u8]) -> Result<Message, Error> {
         if p.len() != util::MESSAGE_SIZE {
-            return Err(Error::InvalidInputLength);
+            return Err(Error::length(Input::Message, util::MESSAGE_SIZE, p.len()));
         }
 
         let mut a = [0; util::MESSAGE_SIZE];
@@ -719,7 +1042,7 @@ impl RecoveryId {
         if p < 4 {
             Ok(RecoveryId(p))
         } else {
-            Err(Error::InvalidRecoveryId)
+            Err(Error::new(ErrorKind::InvalidRecoveryId).with_detail(ErrorDetail::OutOfRange))
         }
     }
 
@@ -728,7 +1051,7 @@ impl RecoveryId {
         if p >= 27 && p < 27 + 4 {
             RecoveryId::parse(p - 27)
         } else {
-            Err(Error::InvalidRecoveryId)
+            Err(Error::new(ErrorKind::InvalidRecoveryId).with_detail(ErrorDetail::OutOfRange))
         }
     }
 
@@ -757,16 +1080,63 @@ impl<D: Digest + Default> SharedSecret<D> {
     ) -> Result<SharedSecret<D>, Error> {
         let inner = match context.ecdh_raw::<D>(&pubkey.0, &seckey.0) {
             Some(val) => val,
-            None => return Err(Error::InvalidSecretKey),
+            None => {
+                return Err(
+                    Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::ZeroScalar)
+                )
+            }
         };
 
         Ok(SharedSecret(inner))
     }
 
-    #[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
     pub fn new(pubkey: &PublicKey, seckey: &SecretKey) -> Result<SharedSecret<D>, Error> {
         Self::new_with_context(pubkey, seckey, &ECMULT_CONTEXT)
     }
+
+    #[cfg(all(feature = "secure-memory", target_os = "linux"))]
+    /// Compute a shared secret into locked memory. The hash is written
+    /// there directly, and the hasher state is wiped afterwards.
+    pub fn new_locked_with_context(
+        pubkey: &PublicKey,
+        seckey: &SecretKey,
+        context: &ECMultContext,
+    ) -> Result<LockedBox<SharedSecret<D>>, Error>
+    where
+        D: digest::Update + digest::FixedOutput<OutputSize = <D as Digest>::OutputSize>,
+    {
+        LockedBox::new_with(SharedSecret(GenericArray::default()), |shared| {
+            if context.ecdh_raw_into::<D>(&pubkey.0, &seckey.0, &mut shared.0) {
+                Ok(())
+            } else {
+                Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::ZeroScalar))
+            }
+        })
+    }
+
+    #[cfg(all(
+        feature = "secure-memory",
+        target_os = "linux",
+        any(
+            feature = "static-context",
+            feature = "lazy-static-context",
+            feature = "const-context"
+        )
+    ))]
+    pub fn new_locked(
+        pubkey: &PublicKey,
+        seckey: &SecretKey,
+    ) -> Result<LockedBox<SharedSecret<D>>, Error>
+    where
+        D: digest::Update + digest::FixedOutput<OutputSize = <D as Digest>::OutputSize>,
+    {
+        Self::new_locked_with_context(pubkey, seckey, &ECMULT_CONTEXT)
+    }
 }
 
 impl<D: Digest> AsRef<[u8]> for SharedSecret<D> {
@@ -775,6 +1145,40 @@ impl<D: Digest> AsRef<[u8]> for SharedSecret<D> {
     }
 }
 
+impl<D: Digest> core::fmt::Debug for SharedSecret<D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SharedSecret(..)")
+    }
+}
+
+impl<D: Digest> PartialEq for SharedSecret<D> {
+    fn eq(&self, other: &SharedSecret<D>) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<D: Digest> Eq for SharedSecret<D> {}
+
+impl<D: Digest> ConstantTimeEq for SharedSecret<D> {
+    fn ct_eq(&self, other: &SharedSecret<D>) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl<D: Digest> Zeroize for SharedSecret<D> {
+    fn zeroize(&mut self) {
+        self.0[..].zeroize();
+    }
+}
+
+impl<D: Digest> Drop for SharedSecret<D> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<D: Digest> ZeroizeOnDrop for SharedSecret<D> {}
+
 /// Check signature is a valid message signed by public key, using the given context.
 pub fn verify_with_context(
     message: &Message,
@@ -785,12 +1189,369 @@ pub fn verify_with_context(
     context.verify_raw(&signature.r, &signature.s, &pubkey.0, &message.0)
 }
 
-#[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
 /// Check signature is a valid message signed by public key.
 pub fn verify(message: &Message, signature: &Signature, pubkey: &PublicKey) -> bool {
     verify_with_context(message, signature, pubkey, &ECMULT_CONTEXT)
 }
 
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// Additional checks made by `verify_detailed`. The default makes none, and
+/// accepts exactly the signatures accepted by `verify`.
+pub struct VerifyOptions {
+    /// Reject signatures whose S is in the upper half of the group order,
+    /// as required by BIP-146 and EIP-2, without having to call
+    /// `Signature::normalize_s` first.
+    pub require_low_s: bool,
+}
+
+/// Check signature is a valid message signed by public key, using the given
+/// context, and return why it is not.
+pub fn verify_detailed_with_context(
+    message: &Message,
+    signature: &Signature,
+    pubkey: &PublicKey,
+    options: VerifyOptions,
+    context: &ECMultContext,
+) -> Result<(), VerifyError> {
+    context.verify_raw_detailed(
+        &signature.r,
+        &signature.s,
+        &pubkey.0,
+        &message.0,
+        options.require_low_s,
+    )
+}
+
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
+/// Check signature is a valid message signed by public key, and return why
+/// it is not.
+pub fn verify_detailed(
+    message: &Message,
+    signature: &Signature,
+    pubkey: &PublicKey,
+    options: VerifyOptions,
+) -> Result<(), VerifyError> {
+    verify_detailed_with_context(message, signature, pubkey, options, &ECMULT_CONTEXT)
+}
+
+#[cfg(feature = "rayon")]
+/// Check many signatures in parallel on the rayon thread pool, using the
+/// given context. The result for every item is the same as the one of
+/// `verify_with_context`.
+pub fn par_verify_with_context(
+    items: &[(Message, Signature, PublicKey)],
+    context: &ECMultContext,
+) -> Vec<bool> {
+    use rayon::prelude::*;
+
+    items
+        .par_iter()
+        .map(|(message, signature, pubkey)| {
+            verify_with_context(message, signature, pubkey, context)
+        })
+        .collect()
+}
+
+#[cfg(all(
+    feature = "rayon",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Check many signatures in parallel on the rayon thread pool. See
+/// `par_verify_with_context`.
+pub fn par_verify(items: &[(Message, Signature, PublicKey)]) -> Vec<bool> {
+    par_verify_with_context(items, &ECMULT_CONTEXT)
+}
+
+#[cfg(feature = "alloc")]
+/// Iterator over the public keys `P, P + S, P + 2*S, ...` for a starting
+/// public key `P = k*G` and a step `S = step*G`, that is, the public keys of
+/// the secret keys `k, k + step, k + 2*step, ...`.
+///
+/// Points are computed `CHUNK_SIZE` at a time with affine additions of the
+/// precomputed multiples of `S`, sharing a single field inversion per chunk,
+/// so each key costs a few field multiplications instead of a full point
+/// multiplication. The sequence ends before the point at infinity, if it is
+/// ever reached. Not constant time.
+#[derive(Debug, Clone)]
+pub struct PublicKeySequence {
+    /// `S, 2*S, ..., CHUNK_SIZE*S`.
+    multiples: Vec<Affine>,
+    /// Points of the current chunk.
+    chunk: Vec<Affine>,
+    /// Index of the next point of `chunk` to yield.
+    pos: usize,
+    /// Last point of `chunk`, from which the next chunk is computed. `None`
+    /// once the point at infinity has been reached.
+    base: Option<Affine>,
+    dx: Vec<Field>,
+    dxi: Vec<Field>,
+}
+
+#[cfg(feature = "alloc")]
+impl PublicKeySequence {
+    /// Number of points computed with each shared field inversion.
+    pub const CHUNK_SIZE: usize = 256;
+
+    /// Create the sequence starting at `start` and advancing by `step*G`,
+    /// using the given context. Use a `step` of one to enumerate
+    /// consecutive secret keys.
+    pub fn new_with_context(
+        start: &PublicKey,
+        step: &SecretKey,
+        context: &ECMultGenContext,
+    ) -> Self {
+        let mut stepj = Jacobian::default();
+        context.ecmult_gen(&mut stepj, &step.0);
+        let mut step_ge = Affine::default();
+        step_ge.set_gej_var(&stepj);
+
+        // None of the multiples is infinity, because `step` is non-zero
+        // and smaller than the group order.
+        let mut multiplesj = vec![Jacobian::default(); Self::CHUNK_SIZE];
+        multiplesj[0] = stepj;
+        for i in 1..Self::CHUNK_SIZE {
+            multiplesj[i] = multiplesj[i - 1].add_ge_var(&step_ge, None);
+        }
+
+        let mut first = start.0;
+        first.x.normalize_var();
+        first.y.normalize_var();
+
+        Self {
+            multiples: set_all_gej_var(&multiplesj),
+            chunk: vec![first],
+            pos: 0,
+            base: Some(first),
+            dx: vec![Field::default(); Self::CHUNK_SIZE],
+            dxi: vec![Field::default(); Self::CHUNK_SIZE],
+        }
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Create the sequence starting at `start` and advancing by `step*G`.
+    pub fn new(start: &PublicKey, step: &SecretKey) -> Self {
+        Self::new_with_context(start, step, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Replace `chunk` with the points `base + S, ..., base + CHUNK_SIZE*S`,
+    /// stopping before the point at infinity.
+    fn next_chunk(&mut self) {
+        self.chunk.clear();
+        self.pos = 0;
+        let base = match self.base {
+            Some(base) => base,
+            None => return,
+        };
+
+        // `base + j*S` with `base = ±j*S` is a doubling or infinity, and is
+        // computed separately below. Use a placeholder for its inverse.
+        for (dx, m) in self.dx.iter_mut().zip(self.multiples.iter()) {
+            *dx = m.x + base.x.neg(1);
+            if dx.normalizes_to_zero_var() {
+                dx.set_int(1);
+            }
+        }
+        inv_all_var_to(&mut self.dxi, &self.dx);
+
+        for (m, dxi) in self.multiples.iter().zip(self.dxi.iter()) {
+            let mut p = Affine::default();
+            if m.x.eq_var(&base.x) {
+                let mut basej = Jacobian::default();
+                basej.set_ge(&base);
+                let pj = basej.add_ge_var(m, None);
+                if pj.is_infinity() {
+                    self.base = None;
+                    return;
+                }
+                p.set_gej_var(&pj);
+                p.x.normalize_var();
+                p.y.normalize_var();
+            } else {
+                p.add_ge_dxinv_var(&base, m, dxi);
+            }
+            self.chunk.push(p);
+        }
+
+        self.base = self.chunk.last().copied();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for PublicKeySequence {
+    type Item = PublicKey;
+
+    fn next(&mut self) -> Option<PublicKey> {
+        if self.pos == self.chunk.len() {
+            self.next_chunk();
+            if self.chunk.is_empty() {
+                return None;
+            }
+        }
+
+        let p = self.chunk[self.pos];
+        self.pos += 1;
+        Some(PublicKey(p))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::iter::FusedIterator for PublicKeySequence {}
+
+#[cfg(feature = "alloc")]
+/// Public key with a precomputed table of its odd multiples, for verifying
+/// many signatures against the same key.
+#[derive(Clone)]
+pub struct PreparedPublicKey {
+    pubkey: PublicKey,
+    window: usize,
+    table: Vec<AffineStorage>,
+}
+
+#[cfg(feature = "alloc")]
+impl PreparedPublicKey {
+    /// Window used by `PreparedPublicKey::new`. The table holds
+    /// `1 << (DEFAULT_WINDOW - 2)` entries of 64 bytes each.
+    pub const DEFAULT_WINDOW: usize = 8;
+    /// Smallest window accepted by `PreparedPublicKey::with_window`, the
+    /// one of the table `verify` computes for each call.
+    pub const MIN_WINDOW: usize = util::WINDOW_A;
+    /// Largest window accepted by `PreparedPublicKey::with_window`.
+    pub const MAX_WINDOW: usize = 20;
+
+    /// Precompute the table for `pubkey` with `DEFAULT_WINDOW`.
+    pub fn new(pubkey: &PublicKey) -> Self {
+        Self::with_window(pubkey, Self::DEFAULT_WINDOW)
+    }
+
+    /// Precompute the table for `pubkey` with the given window. Larger
+    /// windows make each verification faster, at the cost of a table
+    /// that is more expensive to compute and store.
+    ///
+    /// # Panics
+    /// Panics if `window` is not between `MIN_WINDOW` and `MAX_WINDOW`.
+    pub fn with_window(pubkey: &PublicKey, window: usize) -> Self {
+        assert!((Self::MIN_WINDOW..=Self::MAX_WINDOW).contains(&window));
+
+        let mut table = vec![AffineStorage::default(); 1 << (window - 2)];
+        let mut pubkeyj = Jacobian::default();
+        pubkeyj.set_ge(&pubkey.0);
+        odd_multiples_table_storage_var(&mut table, &pubkeyj);
+
+        Self {
+            pubkey: *pubkey,
+            window,
+            table,
+        }
+    }
+
+    /// The public key this table was computed for.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.pubkey
+    }
+
+    /// The window this table was computed for.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Debug for PreparedPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PreparedPublicKey")
+            .field("pubkey", &self.pubkey)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<PublicKey> for PreparedPublicKey {
+    fn from(pubkey: PublicKey) -> Self {
+        Self::new(&pubkey)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Check signature is a valid message signed by a prepared public key,
+/// using the given context.
+pub fn verify_prepared_with_context(
+    message: &Message,
+    signature: &Signature,
+    pubkey: &PreparedPublicKey,
+    context: &ECMultContext,
+) -> bool {
+    context.verify_raw_prepared(
+        &signature.r,
+        &signature.s,
+        &pubkey.table,
+        pubkey.window,
+        &message.0,
+    )
+}
+
+#[cfg(all(
+    feature = "alloc",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Check signature is a valid message signed by a prepared public key.
+pub fn verify_prepared(
+    message: &Message,
+    signature: &Signature,
+    pubkey: &PreparedPublicKey,
+) -> bool {
+    verify_prepared_with_context(message, signature, pubkey, &ECMULT_CONTEXT)
+}
+
+#[cfg(feature = "alloc")]
+/// Check that every signature is a valid signature of its message by a
+/// prepared public key, using the given context. Returns `true` for an
+/// empty batch.
+pub fn verify_batch_prepared_with_context(
+    batch: &[(Message, Signature)],
+    pubkey: &PreparedPublicKey,
+    context: &ECMultContext,
+) -> bool {
+    batch.iter().all(|(message, signature)| {
+        verify_prepared_with_context(message, signature, pubkey, context)
+    })
+}
+
+#[cfg(all(
+    feature = "alloc",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Check that every signature is a valid signature of its message by a
+/// prepared public key. Returns `true` for an empty batch.
+pub fn verify_batch_prepared(batch: &[(Message, Signature)], pubkey: &PreparedPublicKey) -> bool {
+    verify_batch_prepared_with_context(batch, pubkey, &ECMULT_CONTEXT)
+}
+
 /// Recover public key from a signed message, using the given context.
 pub fn recover_with_context(
     message: &Message,
@@ -803,7 +1564,11 @@ pub fn recover_with_context(
         .map(PublicKey)
 }
 
-#[cfg(any(feature = "static-context", feature = "lazy-static-context"))]
+#[cfg(any(
+    feature = "static-context",
+    feature = "lazy-static-context",
+    feature = "const-context"
+))]
 /// Recover public key from a signed message.
 pub fn recover(
     message: &Message,
@@ -813,6 +1578,106 @@ pub fn recover(
     recover_with_context(message, signature, recovery_id, &ECMULT_CONTEXT)
 }
 
+#[cfg(feature = "alloc")]
+/// Recover the public keys of many signed messages, using the given
+/// context. The results are the same as calling `recover_with_context` on
+/// every item, including the errors, but the inverses of `r` and the
+/// affine conversions of the public keys each share a single inversion.
+/// Decompressing `R` still takes one square root per item.
+pub fn recover_batch_with_context(
+    items: &[(Message, Signature, RecoveryId)],
+    context: &ECMultContext,
+) -> Vec<Result<PublicKey, Error>> {
+    let mut sigrs = Vec::with_capacity(items.len());
+    let mut sigss = Vec::with_capacity(items.len());
+    let mut rec_ids = Vec::with_capacity(items.len());
+    let mut messages = Vec::with_capacity(items.len());
+    for (message, signature, recovery_id) in items {
+        sigrs.push(signature.r);
+        sigss.push(signature.s);
+        rec_ids.push(recovery_id.0);
+        messages.push(message.0);
+    }
+
+    context
+        .recover_raw_batch(&sigrs, &sigss, &rec_ids, &messages)
+        .into_iter()
+        .map(|result| result.map(PublicKey))
+        .collect()
+}
+
+#[cfg(all(
+    feature = "alloc",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Recover the public keys of many signed messages. See
+/// `recover_batch_with_context`.
+pub fn recover_batch(items: &[(Message, Signature, RecoveryId)]) -> Vec<Result<PublicKey, Error>> {
+    recover_batch_with_context(items, &ECMULT_CONTEXT)
+}
+
+#[cfg(feature = "rayon")]
+/// Number of items of a parallel batch handled by a single task.
+const PAR_CHUNK_SIZE: usize = 64;
+
+#[cfg(feature = "rayon")]
+/// Same as `recover_batch_with_context`, splitting the items into chunks
+/// that are recovered in parallel on the rayon thread pool.
+pub fn par_recover_with_context(
+    items: &[(Message, Signature, RecoveryId)],
+    context: &ECMultContext,
+) -> Vec<Result<PublicKey, Error>> {
+    use rayon::prelude::*;
+
+    items
+        .par_chunks(PAR_CHUNK_SIZE)
+        .flat_map_iter(|chunk| recover_batch_with_context(chunk, context))
+        .collect()
+}
+
+#[cfg(all(
+    feature = "rayon",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Same as `recover_batch`, recovering chunks of the items in parallel on
+/// the rayon thread pool.
+pub fn par_recover(items: &[(Message, Signature, RecoveryId)]) -> Vec<Result<PublicKey, Error>> {
+    par_recover_with_context(items, &ECMULT_CONTEXT)
+}
+
+#[cfg(feature = "hmac")]
+/// Overwrite the state of `drbg`, which is derived from the secret key,
+/// with a fresh one. `HmacDRBG` does not implement `Zeroize`, so this is a
+/// best-effort overwrite with a volatile write.
+fn wipe_drbg(drbg: &mut HmacDRBG<Sha256>) {
+    // Safety: `drbg` is valid for writes, and `HmacDRBG` has no drop glue,
+    // so overwriting it leaks nothing.
+    unsafe { core::ptr::write_volatile(drbg, HmacDRBG::new(&[], &[], &[])) }
+}
+
+#[cfg(feature = "hmac")]
+/// Draw the next valid nonce from the RFC 6979 deterministic generator.
+fn next_nonce(drbg: &mut HmacDRBG<Sha256>) -> Scalar {
+    let mut nonce = Scalar::default();
+    loop {
+        let mut generated = drbg.generate::<U32>(None);
+        let overflow = bool::from(nonce.set_b32(array_ref!(generated, 0, 32)));
+        generated[..].zeroize();
+
+        if !overflow && !nonce.is_zero() {
+            return nonce;
+        }
+    }
+}
+
 #[cfg(feature = "hmac")]
 /// Sign a message using the secret key, with the given context.
 pub fn sign_with_context(
@@ -820,30 +1685,36 @@ pub fn sign_with_context(
     seckey: &SecretKey,
     context: &ECMultGenContext,
 ) -> (Signature, RecoveryId) {
-    let seckey_b32 = seckey.0.b32();
+    sign_rfc6979(message, seckey, &[], context)
+}
+
+#[cfg(feature = "hmac")]
+/// Sign with the RFC 6979 nonce derived from the secret key, the message
+/// and the `extra` data, which is empty for deterministic signatures.
+fn sign_rfc6979(
+    message: &Message,
+    seckey: &SecretKey,
+    extra: &[u8],
+    context: &ECMultGenContext,
+) -> (Signature, RecoveryId) {
+    let mut seckey_b32 = seckey.0.b32();
     let message_b32 = message.0.b32();
 
-    let mut drbg = HmacDRBG::<Sha256>::new(&seckey_b32, &message_b32, &[]);
-    let mut nonce = Scalar::default();
-    let mut overflow;
+    let mut drbg = HmacDRBG::<Sha256>::new(&seckey_b32, &message_b32, extra);
+    seckey_b32.zeroize();
+    let mut nonce;
 
     let result;
     loop {
-        let generated = drbg.generate::<U32>(None);
-        overflow = bool::from(nonce.set_b32(array_ref!(generated, 0, 32)));
-
-        if !overflow && !nonce.is_zero() {
-            if let Ok(val) = context.sign_raw(&seckey.0, &message.0, &nonce) {
-                result = val;
-                break;
-            }
+        nonce = next_nonce(&mut drbg);
+        if let Ok(val) = context.sign_raw(&seckey.0, &message.0, &nonce) {
+            result = val;
+            break;
         }
     }
 
-    #[allow(unused_assignments)]
-    {
-        nonce = Scalar::default();
-    }
+    nonce.zeroize();
+    wipe_drbg(&mut drbg);
     let (sigr, sigs, recid) = result;
 
     (Signature { r: sigr, s: sigs }, RecoveryId(recid))
@@ -851,13 +1722,250 @@ pub fn sign_with_context(
 
 #[cfg(all(
     feature = "hmac",
-    any(feature = "static-context", feature = "lazy-static-context")
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
 ))]
 /// Sign a message using the secret key.
 pub fn sign(message: &Message, seckey: &SecretKey) -> (Signature, RecoveryId) {
     sign_with_context(message, seckey, &ECMULT_GEN_CONTEXT)
 }
 
+#[cfg(feature = "hmac")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Countermeasures against fault attacks used by `sign_hardened`. Each of
+/// them adds to the cost of signing, and the default enables all of them.
+pub struct Hardening {
+    /// Mix 32 fresh random bytes into the RFC 6979 nonce derivation, so
+    /// that a faulty signature is never computed twice with the same nonce.
+    /// Signatures are then no longer deterministic. Costs one call to the
+    /// random number generator.
+    pub randomize_nonce: bool,
+    /// Recompute the public key from the secret key, and check that it is
+    /// the one recovered from the signature, which verifies both the
+    /// signature and the recovery ID. Makes signing about four times as
+    /// expensive.
+    pub verify: bool,
+}
+
+#[cfg(feature = "hmac")]
+impl Default for Hardening {
+    fn default() -> Self {
+        Hardening {
+            randomize_nonce: true,
+            verify: true,
+        }
+    }
+}
+
+#[cfg(feature = "hmac")]
+/// Sign a message using the secret key with the given contexts, applying
+/// the countermeasures against fault attacks selected by `hardening`.
+/// Returns an `ErrorKind::FaultDetected` error instead of a signature that does not
+/// verify against the public key of `seckey`.
+pub fn sign_hardened_with_context<R: Rng>(
+    message: &Message,
+    seckey: &SecretKey,
+    rng: &mut R,
+    hardening: Hardening,
+    gen_context: &ECMultGenContext,
+    context: &ECMultContext,
+) -> Result<(Signature, RecoveryId), Error> {
+    let mut extra = [0u8; 32];
+    if hardening.randomize_nonce {
+        rng.fill_bytes(&mut extra);
+    }
+    let extra_len = if hardening.randomize_nonce { 32 } else { 0 };
+    let (signature, recovery_id) = sign_rfc6979(message, seckey, &extra[..extra_len], gen_context);
+    extra.zeroize();
+
+    if hardening.verify {
+        let mut pubkey = PublicKey::from_secret_key_with_context(seckey, gen_context);
+        let recovered = recover_with_context(message, &signature, &recovery_id, context);
+        let valid = recovered == Ok(pubkey);
+        pubkey.0.clear();
+        if !valid {
+            return Err(Error::new(ErrorKind::FaultDetected));
+        }
+    }
+
+    Ok((signature, recovery_id))
+}
+
+#[cfg(all(
+    feature = "hmac",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Sign a message using the secret key, applying the countermeasures
+/// against fault attacks selected by `hardening`.
+pub fn sign_hardened<R: Rng>(
+    message: &Message,
+    seckey: &SecretKey,
+    rng: &mut R,
+    hardening: Hardening,
+) -> Result<(Signature, RecoveryId), Error> {
+    sign_hardened_with_context(
+        message,
+        seckey,
+        rng,
+        hardening,
+        &ECMULT_GEN_CONTEXT,
+        &ECMULT_CONTEXT,
+    )
+}
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+/// Sign many messages, each with its own secret key, with the given
+/// context. The result is the same as calling `sign_with_context` on every
+/// pair, but the nonce inversions and the affine conversions of `R` each
+/// share a single inversion across the batch.
+pub fn sign_batch_with_context(
+    items: &[(Message, SecretKey)],
+    context: &ECMultGenContext,
+) -> Vec<(Signature, RecoveryId)> {
+    let mut seckeys = Vec::with_capacity(items.len());
+    let mut messages = Vec::with_capacity(items.len());
+    let mut nonces = Vec::with_capacity(items.len());
+    for (message, seckey) in items {
+        let mut seckey_b32 = seckey.0.b32();
+        let mut drbg = HmacDRBG::<Sha256>::new(&seckey_b32, &message.0.b32(), &[]);
+        seckey_b32.zeroize();
+        nonces.push(next_nonce(&mut drbg));
+        wipe_drbg(&mut drbg);
+        seckeys.push(seckey.0);
+        messages.push(message.0);
+    }
+
+    let results = context.sign_raw_batch(&seckeys, &messages, &nonces);
+    for (seckey, nonce) in seckeys.iter_mut().zip(nonces.iter_mut()) {
+        seckey.clear();
+        nonce.clear();
+    }
+
+    results
+        .into_iter()
+        .zip(items)
+        .map(|(result, (message, seckey))| match result {
+            Ok((sigr, sigs, recid)) => (Signature { r: sigr, s: sigs }, RecoveryId(recid)),
+            // The first nonce gave an invalid signature, which is
+            // astronomically unlikely. Retry with the following nonces.
+            Err(_) => sign_with_context(message, seckey, context),
+        })
+        .collect()
+}
+
+#[cfg(all(
+    feature = "hmac",
+    feature = "alloc",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Sign many messages, each with its own secret key. See
+/// `sign_batch_with_context`.
+pub fn sign_batch(items: &[(Message, SecretKey)]) -> Vec<(Signature, RecoveryId)> {
+    sign_batch_with_context(items, &ECMULT_GEN_CONTEXT)
+}
+
+#[cfg(all(feature = "hmac", feature = "rayon"))]
+/// Same as `sign_batch_with_context`, splitting the items into chunks that
+/// are signed in parallel on the rayon thread pool.
+pub fn par_sign_batch_with_context(
+    items: &[(Message, SecretKey)],
+    context: &ECMultGenContext,
+) -> Vec<(Signature, RecoveryId)> {
+    use rayon::prelude::*;
+
+    items
+        .par_chunks(PAR_CHUNK_SIZE)
+        .flat_map_iter(|chunk| sign_batch_with_context(chunk, context))
+        .collect()
+}
+
+#[cfg(all(
+    feature = "hmac",
+    feature = "rayon",
+    any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    )
+))]
+/// Same as `sign_batch`, signing chunks of the items in parallel on the
+/// rayon thread pool.
+pub fn par_sign_batch(items: &[(Message, SecretKey)]) -> Vec<(Signature, RecoveryId)> {
+    par_sign_batch_with_context(items, &ECMULT_GEN_CONTEXT)
+}
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+/// An `ECMultGenContext` whose blinding is refreshed with fresh randomness
+/// on a schedule. See `ECMultGenContext::randomize`.
+pub struct RandomizedGenContext<R: Rng> {
+    context: Box<ECMultGenContext>,
+    rng: R,
+    interval: u32,
+    uses: u32,
+}
+
+#[cfg(all(feature = "hmac", feature = "alloc"))]
+impl<R: Rng> RandomizedGenContext<R> {
+    /// Copy `context` and randomize the copy using `rng`. The blinding is
+    /// refreshed again after every `interval` calls to `context`. An
+    /// `interval` of zero disables automatic refreshing.
+    pub fn new(context: &ECMultGenContext, mut rng: R, interval: u32) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let context = context.randomized_boxed(&seed);
+
+        seed.zeroize();
+
+        Self {
+            context,
+            rng,
+            interval,
+            uses: 0,
+        }
+    }
+
+    #[cfg(any(
+        feature = "static-context",
+        feature = "lazy-static-context",
+        feature = "const-context"
+    ))]
+    /// Copy the static `ECMULT_GEN_CONTEXT` and randomize the copy. See `new`.
+    pub fn from_static(rng: R, interval: u32) -> Self {
+        Self::new(&ECMULT_GEN_CONTEXT, rng, interval)
+    }
+
+    /// Refresh the blinding now.
+    pub fn randomize(&mut self) {
+        let mut seed = [0u8; 32];
+        self.rng.fill_bytes(&mut seed);
+        self.context.randomize(&seed);
+        self.uses = 0;
+
+        seed.zeroize();
+    }
+
+    /// Get the context for one operation, refreshing its blinding first if
+    /// it has already been used `interval` times.
+    pub fn context(&mut self) -> &ECMultGenContext {
+        if self.interval != 0 && self.uses >= self.interval {
+            self.randomize();
+        }
+        self.uses += 1;
+        &self.context
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::SecretKey;
@@ -0,0 +1,197 @@
+//! Locked-memory storage for secrets, available with the `secure-memory`
+//! feature on Linux.
+
+use core::{
+    convert::TryFrom,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+use rand::Rng;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{util, Error, ErrorDetail, ErrorKind, Input, SecretKey};
+
+/// A heap value kept in its own `mlock`'d pages, so that it is never
+/// swapped out, excluded from core dumps with `MADV_DONTDUMP`, and placed
+/// right before an inaccessible guard page, with another one before the
+/// pages. The value is zeroized when dropped, and its `Debug` output does
+/// not contain it.
+///
+/// `LockedBox<SecretKey>` dereferences to `SecretKey`, so it can be passed
+/// directly to `sign_with_context`, `SharedSecret::new_with_context` and
+/// the other functions taking a `&SecretKey`, without copying the key out.
+///
+/// Only the value itself is locked. Signing and ECDH still work on the
+/// stack, and the intermediate values they derive from the key are wiped
+/// afterwards where this crate owns them: the scalars and points, the
+/// encoded key and shared point, the `HmacDRBG` state and the ECDH hasher.
+/// The temporary HMAC and hash states inside the `hmac-drbg`, `hmac` and
+/// digest crates cannot be reached and are left on the stack, so a core
+/// dump or swapped-out stack page taken during a signature or key exchange
+/// can still contain values derived from the key.
+pub struct LockedBox<T: Zeroize> {
+    map: NonNull<u8>,
+    map_len: usize,
+    page: usize,
+    value: NonNull<T>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Zeroize + Send> Send for LockedBox<T> {}
+unsafe impl<T: Zeroize + Sync> Sync for LockedBox<T> {}
+
+impl<T: Zeroize> LockedBox<T> {
+    /// Move a value into locked memory. The moved-from value is not
+    /// cleared, so use `new_with` to write secrets directly into the locked
+    /// pages instead.
+    pub fn new(value: T) -> Result<Self, Error> {
+        Self::new_with(value, |_| Ok(()))
+    }
+
+    /// Move a value that holds no secret yet into locked memory, then let
+    /// `init` fill it in place. If `init` fails, the value is zeroized and
+    /// its error returned.
+    pub fn new_with<F>(value: T, init: F) -> Result<Self, Error>
+    where
+        F: FnOnce(&mut T) -> Result<(), Error>,
+    {
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page <= 0 || mem::align_of::<T>() > page as usize {
+            return Err(Error::new(ErrorKind::SecureMemory));
+        }
+        let page = page as usize;
+        let size = mem::size_of::<T>().max(1);
+        let data_len = size.div_ceil(page) * page;
+        let map_len = data_len + 2 * page;
+
+        let map = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                map_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if map == libc::MAP_FAILED {
+            return Err(Error::new(ErrorKind::SecureMemory));
+        }
+        let map = map as *mut u8;
+        let data = unsafe { map.add(page) };
+
+        let ok = unsafe {
+            libc::mprotect(map as *mut libc::c_void, page, libc::PROT_NONE) == 0
+                && libc::mprotect(
+                    data.add(data_len) as *mut libc::c_void,
+                    page,
+                    libc::PROT_NONE,
+                ) == 0
+                && libc::madvise(data as *mut libc::c_void, data_len, libc::MADV_DONTDUMP) == 0
+                && libc::mlock(data as *const libc::c_void, data_len) == 0
+        };
+        if !ok {
+            unsafe {
+                libc::munmap(map as *mut libc::c_void, map_len);
+            }
+            return Err(Error::new(ErrorKind::SecureMemory));
+        }
+
+        // Put the value at the end of the pages, so that overflowing it
+        // faults on the guard page.
+        let offset = (data_len - mem::size_of::<T>()) & !(mem::align_of::<T>() - 1);
+        let value_ptr = unsafe { data.add(offset) as *mut T };
+        unsafe {
+            ptr::write(value_ptr, value);
+        }
+
+        let mut ret = LockedBox {
+            map: unsafe { NonNull::new_unchecked(map) },
+            map_len,
+            page,
+            value: unsafe { NonNull::new_unchecked(value_ptr) },
+            _marker: PhantomData,
+        };
+        init(&mut ret)?;
+        Ok(ret)
+    }
+}
+
+impl LockedBox<SecretKey> {
+    /// Parse a secret key directly into locked memory.
+    pub fn parse(p: &[u8; util::SECRET_KEY_SIZE]) -> Result<Self, Error> {
+        Self::new_with(SecretKey::default(), |key| {
+            let overflowed = bool::from(key.0.set_b32(p));
+            if overflowed {
+                Err(Error::new(ErrorKind::InvalidSecretKey)
+                    .with_detail(ErrorDetail::ScalarOverflow))
+            } else if key.0.is_zero() {
+                Err(Error::new(ErrorKind::InvalidSecretKey).with_detail(ErrorDetail::ZeroScalar))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    pub fn parse_slice(p: &[u8]) -> Result<Self, Error> {
+        match <&[u8; util::SECRET_KEY_SIZE]>::try_from(p) {
+            Ok(p) => Self::parse(p),
+            Err(_) => Err(Error::length(
+                Input::SecretKey,
+                util::SECRET_KEY_SIZE,
+                p.len(),
+            )),
+        }
+    }
+
+    /// Generate a random secret key directly into locked memory. The random
+    /// bytes are also kept in locked memory.
+    pub fn random<R: Rng>(rng: &mut R) -> Result<Self, Error> {
+        let mut bytes = LockedBox::new([0u8; util::SECRET_KEY_SIZE])?;
+        Self::new_with(SecretKey::default(), |key| loop {
+            rng.fill_bytes(&mut bytes[..]);
+            let overflowed = bool::from(key.0.set_b32(&bytes));
+            if !overflowed && !key.0.is_zero() {
+                return Ok(());
+            }
+        })
+    }
+}
+
+impl<T: Zeroize> Deref for LockedBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: Zeroize> DerefMut for LockedBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: Zeroize> core::fmt::Debug for LockedBox<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("LockedBox(..)")
+    }
+}
+
+impl<T: Zeroize> Drop for LockedBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.value.as_mut().zeroize();
+            ptr::drop_in_place(self.value.as_ptr());
+            libc::munlock(
+                self.map.as_ptr().add(self.page) as *const libc::c_void,
+                self.map_len - 2 * self.page,
+            );
+            libc::munmap(self.map.as_ptr() as *mut libc::c_void, self.map_len);
+        }
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for LockedBox<T> {}
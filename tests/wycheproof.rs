@@ -99,6 +99,15 @@ fn test_wycheproof() {
         let key_raw = hex::decode(test_group.key.uncompressed).unwrap();
         let key = libsecp256k1::PublicKey::parse_slice(&key_raw, None).unwrap();
 
+        let key_der = hex::decode(&test_group.key_der).unwrap();
+        assert_eq!(libsecp256k1::PublicKey::parse_der(&key_der).unwrap(), key);
+        assert_eq!(&key.serialize_der()[..], &key_der[..]);
+        assert_eq!(
+            libsecp256k1::PublicKey::parse_pem(&test_group.key_pem).unwrap(),
+            key
+        );
+        assert_eq!(key.to_pem().trim_end(), test_group.key_pem);
+
         for test in test_group.tests {
             let res = test_unit(&test, &key);
 